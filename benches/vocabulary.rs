@@ -0,0 +1,76 @@
+// throughput of the vocabulary lookups on a synthetic corpus of 100MB (set
+// 'DANTE_BENCH_MB' to change its size) with a 200k words vocabulary:
+//
+//     cargo bench --bench vocabulary
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use dante::{BasicTokenizer, PreTokenizerKind, Tokenizer, Vocabulary};
+
+const VOCABULARY_SIZE: usize = 200_000;
+const LINE_SIZE: usize = 1 << 20;
+
+fn word(mut i: usize) -> String {
+    let mut word = String::new();
+    loop {
+        word.push((b'a' + (i % 26) as u8) as char);
+        i /= 26;
+        if i == 0 {
+            return word;
+        }
+    }
+}
+
+// lines of about 1MB, frequent words are drawn more often
+fn corpus(size: usize) -> Vec<String> {
+    let words: Vec<String> = (0..VOCABULARY_SIZE).map(word).collect();
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut lines: Vec<String> = Vec::new();
+    let mut total = 0;
+    while total < size {
+        let mut line = String::with_capacity(LINE_SIZE + 16);
+        while line.len() < LINE_SIZE {
+            let i = (rng.gen::<f64>().powi(3) * VOCABULARY_SIZE as f64) as usize;
+            line.push_str(&words[i]);
+            line.push(' ');
+        }
+        total += line.len();
+        lines.push(line);
+    }
+
+    lines
+}
+
+fn vocabulary(c: &mut Criterion) {
+    let size: usize = std::env::var("DANTE_BENCH_MB").ok().and_then(|s| s.parse().ok()).unwrap_or(100) << 20;
+    let lines = corpus(size);
+    let bytes: usize = lines.iter().map(|l| l.len()).sum();
+
+    let mut group = c.benchmark_group("vocabulary");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(bytes as u64));
+
+    // fitted once, the vocabulary is rebuilt at the end of every fit
+    let mut tokenizer = BasicTokenizer::new_(None, Some(&[PreTokenizerKind::WhiteSpace]));
+    tokenizer.fit_iter_(lines.iter().map(|line| Ok(line.to_string()))).unwrap();
+
+    group.bench_function("transform", |b| b.iter(|| {
+        lines.iter().map(|line| tokenizer.transform_(line).unwrap().len()).sum::<usize>()
+    }));
+    group.bench_function("encode", |b| b.iter(|| {
+        lines.iter().map(|line| tokenizer.encode_(line).unwrap().len()).sum::<usize>()
+    }));
+
+    let words: Vec<String> = lines.iter().flat_map(|l| l.split_whitespace().map(|w| w.to_string())).collect();
+    group.throughput(Throughput::Elements(words.len() as u64));
+    group.bench_function("lookup", |b| {
+        let mut vocabulary = Vocabulary::new();
+        vocabulary.extend((0..VOCABULARY_SIZE).map(word));
+        b.iter(|| words.iter().filter(|w| vocabulary.contains(w)).count())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, vocabulary);
+criterion_main!(benches);
@@ -1,8 +1,13 @@
 use pyo3::prelude::*;
 
 use tokenization::{
-    tokenizers::basic::BasicTokenizer,
-    pre_tokenizers::PreTokenizerKind
+    encoding::{Encoding, PaddingDirection, TruncationStrategy},
+    tokenizers::{bpe::BPE, unigram::Unigram, wordpiece::WordPiece},
+    normalizers::NormalizerKind,
+    pre_tokenizers::{stem::StemLanguage, SplitBehavior},
+    rules::Language,
+    template::Template,
+    sentences::SentenceSplitter
 };
 use models::{language::mle::MLE, classification::naive_bayes::NaiveBayesClassifier};
 
@@ -10,10 +15,29 @@ mod models;
 mod tokenization;
 mod utils;
 
+// what the benchmarks use from Rust
+pub use tokenization::{
+    pre_tokenizers::PreTokenizerKind,
+    tokenizers::{basic::BasicTokenizer, tokenizer::Tokenizer},
+    vocabulary::Vocabulary
+};
+
 #[pymodule]
 fn dante(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<BasicTokenizer>()?;
+    m.add_class::<BPE>()?;
+    m.add_class::<WordPiece>()?;
+    m.add_class::<Unigram>()?;
+    m.add_class::<Encoding>()?;
+    m.add_class::<PaddingDirection>()?;
+    m.add_class::<TruncationStrategy>()?;
+    m.add_class::<Template>()?;
+    m.add_class::<NormalizerKind>()?;
     m.add_class::<PreTokenizerKind>()?;
+    m.add_class::<SplitBehavior>()?;
+    m.add_class::<Language>()?;
+    m.add_class::<StemLanguage>()?;
+    m.add_class::<SentenceSplitter>()?;
 
     m.add_class::<MLE>()?;
     m.add_class::<NaiveBayesClassifier>()?;
@@ -1,13 +1,11 @@
-use std::collections::HashMap;
-
-use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
 use pyo3::{pyclass, pymethods};
 
 use crate::tokenization::token::Word;
 
 #[pyclass]
 pub struct NaiveBayesClassifier {
-    vocabulary: Vec<Word>,
+    vocabulary: HashSet<Word>,
     // Note that 'priors' and 'likelihood' are expressed as log
     priors: HashMap<String, f32>,
     likelihood: HashMap<String, HashMap<Word, f32>>
@@ -16,7 +14,7 @@ pub struct NaiveBayesClassifier {
 impl NaiveBayesClassifier {
     fn new_() -> Self {
         NaiveBayesClassifier {
-            vocabulary: vec![],
+            vocabulary: HashSet::new(),
             priors: HashMap::new(),
             likelihood: HashMap::new()
         }
@@ -28,8 +26,8 @@ impl NaiveBayesClassifier {
         let n_doc: usize = all_docs.len();
         
         self.vocabulary = all_docs.into_iter()
-            .flat_map(|v| v.to_owned())
-            .unique()
+            .flatten()
+            .cloned()
             .collect();
  
         self.priors = train_set.iter()
@@ -47,9 +45,13 @@ impl NaiveBayesClassifier {
             .fold(HashMap::new(), |mut likelihood, class| {
                 let label = class.0.to_owned();
                 let class_corpus: Vec<&Word> = class.1.iter().flatten().collect();
+                let counts: HashMap<&Word, usize> = class_corpus.iter().fold(HashMap::new(), |mut counts, word| {
+                    *counts.entry(*word).or_insert(0) += 1;
+                    counts
+                });
 
                 let class_likelihood = self.vocabulary.iter().fold(HashMap::new(), |mut class_likelihood, word| {
-                    let count: usize = class_corpus.iter().filter(|w| **w == word).count();
+                    let count: usize = counts.get(word).copied().unwrap_or(0);
 
                     let log_likelihood = f32::log2((count as f32 + 1.0) / (class_corpus.len() + self.vocabulary.len()) as f32);
                     class_likelihood.insert(word.to_owned(), log_likelihood);
@@ -62,7 +64,7 @@ impl NaiveBayesClassifier {
             });
     }
 
-    fn predict_(&self, test_set: &Vec<Word>) -> String {
+    fn predict_(&self, test_set: &[Word]) -> String {
         self.priors.iter()
         .map(|prior| {
             let class = prior.0;
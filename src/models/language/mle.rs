@@ -10,6 +10,7 @@ use crate::tokenization::token::Word;
 
 
 // MLE stands for "Maximum Likelihood Estimation"
+#[allow(clippy::upper_case_acronyms)]
 #[pyclass]
 pub struct MLE {
     n: u32,
@@ -33,8 +34,13 @@ impl MLE {
     }
 
     // transform text into n-grams and add the context counts in a lookup table
-    fn fit_(&mut self, text: &Vec<Word>, vocabulary: &Vec<Word>) {
-        self.ngrams = ngrams(text, self.n, true);
+    fn fit_(&mut self, text: &[Word], vocabulary: &[Word]) {
+        self.fit_sentences_(&[text.to_vec()], vocabulary)
+    }
+
+    // same as 'fit_', every sentence is padded on its own
+    fn fit_sentences_(&mut self, sentences: &[Vec<Word>], vocabulary: &[Word]) {
+        self.ngrams = sentences.iter().flat_map(|sentence| ngrams(sentence, self.n, true)).collect();
         for ngram in self.ngrams.iter() {
             let (word, context) = ngram.split_last().unwrap();
             self.context_counter.entry(context.to_vec()).or_insert(vec![]).push(word.to_owned());
@@ -43,7 +49,7 @@ impl MLE {
     }
 
     // generate a word using the provided sentence.
-    fn generate_word_(&self, sentence: &Vec<Word>) -> Word {
+    fn generate_word_(&self, sentence: &[Word]) -> Word {
         let context: Vec<Word> = sentence[(sentence.len() + 1 - self.n as usize)..].to_vec();
 
         let possible_words: Vec<Word> = match self.context_counter.get(&context) {
@@ -51,26 +57,26 @@ impl MLE {
             None => vec![]
         };
 
-        if possible_words.len() == 0 {
+        if possible_words.is_empty() {
             return special_tokens::UNK.to_owned();
         }
 
         let mut rng = thread_rng();
-        let choices: Vec<(&Word, f32)> = possible_words.iter().map(|w| (w, self.score(&context, &w))).collect();
+        let choices: Vec<(&Word, f32)> = possible_words.iter().map(|w| (w, self.score(&context, w))).collect();
         let dist = WeightedIndex::new(choices.iter().map(|choice| choice.1)).unwrap();
 
         choices[dist.sample(&mut rng)].0.to_owned()
     }
 
     // get the frequency of a word after a specific context.
-    fn score(&self, context: &Vec<Word>, word: &Word) -> f32 {
+    fn score(&self, context: &[Word], word: &Word) -> f32 {
         let possible_words: Vec<Word> = match self.context_counter.get(context) {
             Some(w) => w.to_owned(),
             None => vec![]
         };
         let word_count: usize = possible_words.iter().filter(|possible_word| *possible_word == word).count();
 
-        if possible_words.len() == 0 || word_count == 0 {
+        if possible_words.is_empty() || word_count == 0 {
             return 0.0;
         }
 
@@ -78,21 +84,21 @@ impl MLE {
     }
 
     // compute the entropy of the model given a test set
-    fn entropy_(&self, test_set: &Vec<Word>) -> f32 {
+    fn entropy_(&self, test_set: &[Word]) -> f32 {
         let ngrams: Vec<Vec<Word>> = ngrams(test_set, self.n, true);
         let mut total_score: f32 = 0.0;
 
         for ngram in ngrams.iter() {
             let (word, context) = ngram.split_last().unwrap();
-            let score = self.score(&context.to_vec(), word);
+            let score = self.score(context, word);
             total_score += if score == 0.0 {0.0} else {f32::log2(score)};
         }
         
-        -1.0 * (total_score / ngrams.len() as f32)
+        -(total_score / ngrams.len() as f32)
     }
 
     // compute the perplexity of the model given a test set
-    fn perplexity_(&self, test_set: &Vec<Word>) -> f32 {
+    fn perplexity_(&self, test_set: &[Word]) -> f32 {
         f32::powf(2.0, self.entropy_(test_set))
     }
 }
@@ -108,15 +114,19 @@ impl MLE {
         MLE::fit_(self, &text, &vocabulary)
     }
 
+    fn fit_sentences(&mut self, sentences: Vec<Vec<Word>>, vocabulary: Vec<Word>) {
+        MLE::fit_sentences_(self, &sentences, &vocabulary)
+    }
+
     fn generate_word(&mut self, sentence: Vec<Word>) -> Word {
-        MLE::generate_word_(&self, &sentence)
+        MLE::generate_word_(self, &sentence)
     }
 
     fn entropy(&self, test_set: Vec<Word>) -> f32 {
-        MLE::entropy_(&self, &test_set)
+        MLE::entropy_(self, &test_set)
     }
 
     fn perplexity(&self, test_set: Vec<Word>) -> f32 {
-        MLE::perplexity_(&self, &test_set)
+        MLE::perplexity_(self, &test_set)
     }
 }
@@ -0,0 +1,344 @@
+use pyo3::{pyclass, pymethods, PyResult};
+
+use crate::tokenization::{
+    token::{Token, Word},
+    vocabulary::Vocabulary
+};
+use crate::utils::errors::{Error, Result};
+
+#[derive(Clone, Debug, PartialEq)]
+#[pyclass]
+pub enum PaddingDirection {
+    Left,
+    Right,
+}
+
+// how the tokens of a pair of sequences are removed to fit a maximum length
+#[derive(Clone, Debug, PartialEq)]
+#[pyclass]
+pub enum TruncationStrategy {
+    // one token at a time from the longest sequence
+    LongestFirst,
+    OnlyFirst,
+    OnlySecond,
+}
+
+// ids of a sequence along with what a model needs to consume them
+#[derive(Clone, Debug, Default, PartialEq)]
+#[pyclass]
+pub struct Encoding {
+    #[pyo3(get)]
+    pub ids: Vec<u32>,
+    #[pyo3(get)]
+    pub tokens: Vec<Word>,
+    // byte span of every token in the original text, (0, 0) for padding
+    #[pyo3(get)]
+    pub offsets: Vec<(usize, usize)>,
+    // segment of every token: 0 for the first sequence, usually 1 for the second
+    #[pyo3(get)]
+    pub type_ids: Vec<u32>,
+    // 0 for padding
+    #[pyo3(get)]
+    pub attention_mask: Vec<u32>,
+    // 1 for special tokens and padding
+    #[pyo3(get)]
+    pub special_tokens_mask: Vec<u32>,
+    // windows of the tokens removed by truncation
+    #[pyo3(get)]
+    pub overflowing: Vec<Encoding>,
+}
+
+impl Encoding {
+    // tokens missing from the vocabulary get the id of '<unk>', or are
+    // dropped when there is none, as in 'encode_'
+    pub fn from_tokens(tokens: &[Token], vocabulary: &Vocabulary) -> Self {
+        let mut encoding = Encoding::default();
+        for token in tokens.iter() {
+            if let Some(id) = vocabulary.id(&token.word).or(vocabulary.unk_id()) {
+                encoding.ids.push(id);
+                encoding.tokens.push(token.word.to_owned());
+                encoding.offsets.push((token.start, token.end));
+                encoding.type_ids.push(0);
+                encoding.attention_mask.push(1);
+                encoding.special_tokens_mask.push(token.special as u32);
+            }
+        }
+
+        encoding
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    fn slice(&self, from: usize, to: usize) -> Encoding {
+        Encoding {
+            ids: self.ids[from..to].to_vec(),
+            tokens: self.tokens[from..to].to_vec(),
+            offsets: self.offsets[from..to].to_vec(),
+            type_ids: self.type_ids[from..to].to_vec(),
+            attention_mask: self.attention_mask[from..to].to_vec(),
+            special_tokens_mask: self.special_tokens_mask[from..to].to_vec(),
+            overflowing: vec![],
+        }
+    }
+
+    // keep the first 'max_length' tokens, the rest goes to overflowing windows
+    // of the same size that repeat the last 'stride' tokens of the previous one
+    pub fn truncate_(&mut self, max_length: usize, stride: usize) -> Result<()> {
+        if self.len() <= max_length {
+            return Ok(());
+        }
+        if max_length == 0 {
+            *self = Encoding { overflowing: vec![self.slice(0, self.len())], ..Default::default() };
+            return Ok(());
+        }
+        if stride >= max_length {
+            return Err(Error::Invalid(format!("stride ({}) must be lower than max_length ({})", stride, max_length)));
+        }
+
+        let step = max_length - stride;
+        let mut overflowing: Vec<Encoding> = Vec::new();
+        let mut start = step;
+        while start + stride < self.len() {
+            overflowing.push(self.slice(start, (start + max_length).min(self.len())));
+            start += step;
+        }
+
+        *self = Encoding { overflowing, ..self.slice(0, max_length) };
+        Ok(())
+    }
+
+    // truncate two sequences so that together they fit in 'max_length'
+    pub fn truncate_pair_(
+        first: &mut Encoding,
+        second: &mut Encoding,
+        max_length: usize,
+        strategy: &TruncationStrategy,
+        stride: usize
+    ) -> Result<()> {
+        let total = first.len() + second.len();
+        if total <= max_length {
+            return Ok(());
+        }
+
+        let (first_length, second_length) = match strategy {
+            TruncationStrategy::LongestFirst => {
+                let shortest = first.len().min(second.len());
+                let half = max_length / 2;
+                if shortest <= half {
+                    if first.len() <= second.len() { (first.len(), max_length - first.len()) }
+                    else { (max_length - second.len(), second.len()) }
+                } else if first.len() >= second.len() {
+                    (max_length - half, half)
+                } else {
+                    (half, max_length - half)
+                }
+            },
+            TruncationStrategy::OnlyFirst => match max_length.checked_sub(second.len()) {
+                Some(length) => (length, second.len()),
+                None => return Err(Error::Invalid("the second sequence is longer than max_length".to_string()))
+            },
+            TruncationStrategy::OnlySecond => match max_length.checked_sub(first.len()) {
+                Some(length) => (first.len(), length),
+                None => return Err(Error::Invalid("the first sequence is longer than max_length".to_string()))
+            },
+        };
+
+        // both sides are checked before any of them is truncated
+        for (encoding, length) in [(&*first, first_length), (&*second, second_length)] {
+            if encoding.len() > length && length > 0 && stride >= length {
+                return Err(Error::Invalid(format!("stride ({}) must be lower than the kept length ({})", stride, length)));
+            }
+        }
+
+        first.truncate_(first_length, stride)?;
+        second.truncate_(second_length, stride)
+    }
+
+    pub fn pad_(&mut self, length: usize, pad_id: u32, pad_token: &str, direction: &PaddingDirection) {
+        for overflowing in self.overflowing.iter_mut() {
+            overflowing.pad_(length, pad_id, pad_token, direction);
+        }
+        if self.len() >= length {
+            return;
+        }
+
+        let missing = length - self.len();
+        let pad = |values: &mut Vec<_>, value| {
+            let padding = std::iter::repeat_n(value, missing);
+            match direction {
+                PaddingDirection::Left => { values.splice(0..0, padding); },
+                PaddingDirection::Right => values.extend(padding),
+            }
+        };
+        pad(&mut self.ids, pad_id);
+        pad(&mut self.type_ids, 0);
+        pad(&mut self.attention_mask, 0);
+        pad(&mut self.special_tokens_mask, 1);
+        let tokens = std::iter::repeat_n(pad_token.to_string(), missing);
+        let offsets = std::iter::repeat_n((0, 0), missing);
+        match direction {
+            PaddingDirection::Left => {
+                self.tokens.splice(0..0, tokens);
+                self.offsets.splice(0..0, offsets);
+            },
+            PaddingDirection::Right => {
+                self.tokens.extend(tokens);
+                self.offsets.extend(offsets);
+            },
+        }
+    }
+
+    // pad every encoding to 'length', or to the longest one when there is
+    // none, rounded up to a multiple of 'pad_to_multiple_of'
+    pub fn pad_batch_(
+        encodings: &mut [Encoding],
+        length: Option<usize>,
+        pad_to_multiple_of: Option<usize>,
+        pad_id: u32,
+        pad_token: &str,
+        direction: &PaddingDirection
+    ) {
+        let mut length = length.unwrap_or_else(|| {
+            encodings
+                .iter()
+                .flat_map(|e| std::iter::once(e.len()).chain(e.overflowing.iter().map(|o| o.len())))
+                .max()
+                .unwrap_or(0)
+        });
+        if let Some(multiple) = pad_to_multiple_of.filter(|m| *m > 0) {
+            length = length.div_ceil(multiple) * multiple;
+        }
+
+        for encoding in encodings.iter_mut() {
+            encoding.pad_(length, pad_id, pad_token, direction);
+        }
+    }
+}
+
+#[pymethods]
+impl Encoding {
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+
+    #[args(stride = "0")]
+    fn truncate(&mut self, max_length: usize, stride: usize) -> PyResult<()> {
+        Ok(Encoding::truncate_(self, max_length, stride)?)
+    }
+
+    #[args(pad_token = "\"<pad>\"", direction = "PaddingDirection::Right")]
+    fn pad(&mut self, length: usize, pad_id: u32, pad_token: &str, direction: PaddingDirection) {
+        Encoding::pad_(self, length, pad_id, pad_token, &direction)
+    }
+
+    #[staticmethod]
+    #[args(strategy = "TruncationStrategy::LongestFirst", stride = "0")]
+    fn truncate_pair(
+        mut first: Encoding,
+        mut second: Encoding,
+        max_length: usize,
+        strategy: TruncationStrategy,
+        stride: usize
+    ) -> PyResult<(Encoding, Encoding)> {
+        Encoding::truncate_pair_(&mut first, &mut second, max_length, &strategy, stride)?;
+        Ok((first, second))
+    }
+
+    #[staticmethod]
+    #[args(
+        length = "None",
+        pad_to_multiple_of = "None",
+        pad_token = "\"<pad>\"",
+        direction = "PaddingDirection::Right"
+    )]
+    fn pad_batch(
+        mut encodings: Vec<Encoding>,
+        pad_id: u32,
+        length: Option<usize>,
+        pad_to_multiple_of: Option<usize>,
+        pad_token: &str,
+        direction: PaddingDirection
+    ) -> Vec<Encoding> {
+        Encoding::pad_batch_(&mut encodings, length, pad_to_multiple_of, pad_id, pad_token, &direction);
+        encodings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoding(ids: &[u32]) -> Encoding {
+        let tokens: Vec<Token> = ids
+            .iter()
+            .map(|id| Token { word: id.to_string(), start: *id as usize, end: *id as usize + 1, ..Default::default() })
+            .collect();
+        let mut vocabulary = Vocabulary::empty();
+        vocabulary.extend((0..10).map(|id| id.to_string()));
+        Encoding::from_tokens(&tokens, &vocabulary)
+    }
+
+    #[test]
+    fn overflowing_windows() {
+        let mut sequence = encoding(&[0, 1, 2, 3, 4, 5, 6]);
+        sequence.truncate_(3, 1).unwrap();
+
+        assert_eq!(sequence.ids, vec![0, 1, 2]);
+        let windows: Vec<Vec<u32>> = sequence.overflowing.iter().map(|o| o.ids.to_vec()).collect();
+        assert_eq!(windows, vec![vec![2, 3, 4], vec![4, 5, 6]]);
+        assert!(matches!(sequence.truncate_(2, 2), Err(Error::Invalid(_))));
+    }
+
+    #[test]
+    fn pair_truncation() {
+        let (mut first, mut second) = (encoding(&[0, 1, 2, 3, 4, 5]), encoding(&[6, 7]));
+        Encoding::truncate_pair_(&mut first, &mut second, 5, &TruncationStrategy::LongestFirst, 0).unwrap();
+        assert_eq!((first.ids, second.ids), (vec![0, 1, 2], vec![6, 7]));
+
+        let (mut first, mut second) = (encoding(&[0, 1, 2, 3]), encoding(&[4, 5, 6, 7]));
+        Encoding::truncate_pair_(&mut first, &mut second, 5, &TruncationStrategy::LongestFirst, 0).unwrap();
+        assert_eq!((first.len(), second.len()), (3, 2));
+
+        let (mut first, mut second) = (encoding(&[0, 1]), encoding(&[2, 3, 4, 5]));
+        let result = Encoding::truncate_pair_(&mut first, &mut second, 3, &TruncationStrategy::OnlyFirst, 0);
+        assert!(matches!(result, Err(Error::Invalid(_))));
+        Encoding::truncate_pair_(&mut first, &mut second, 3, &TruncationStrategy::OnlySecond, 0).unwrap();
+        assert_eq!((first.ids, second.ids), (vec![0, 1], vec![2]));
+    }
+
+    #[test]
+    fn pair_stride() {
+        let (mut first, mut second) = (encoding(&[0, 1, 2, 3, 4, 5]), encoding(&[6, 7, 8, 9]));
+        Encoding::truncate_pair_(&mut first, &mut second, 6, &TruncationStrategy::LongestFirst, 1).unwrap();
+        assert_eq!((first.ids.to_vec(), second.ids.to_vec()), (vec![0, 1, 2], vec![6, 7, 8]));
+        assert_eq!(first.overflowing[0].ids, vec![2, 3, 4]);
+        assert_eq!(second.overflowing[0].ids, vec![8, 9]);
+
+        // the stride is too large for the second sequence, nothing is truncated
+        let (mut first, mut second) = (encoding(&[0, 1, 2, 3, 4, 5]), encoding(&[6, 7, 8, 9]));
+        let result = Encoding::truncate_pair_(&mut first, &mut second, 7, &TruncationStrategy::OnlySecond, 1);
+        assert!(matches!(result, Err(Error::Invalid(_))));
+        assert_eq!((first.len(), second.len()), (6, 4));
+    }
+
+    #[test]
+    fn padding() {
+        let mut batch = vec![encoding(&[1, 2, 3]), encoding(&[4])];
+        Encoding::pad_batch_(&mut batch, None, Some(4), 9, "<pad>", &PaddingDirection::Right);
+        assert_eq!(batch[0].ids, vec![1, 2, 3, 9]);
+        assert_eq!(batch[1].attention_mask, vec![1, 0, 0, 0]);
+
+        let mut sequence = encoding(&[4]);
+        sequence.pad_(3, 9, "<pad>", &PaddingDirection::Left);
+        assert_eq!(sequence.ids, vec![9, 9, 4]);
+        assert_eq!(sequence.tokens, vec!["<pad>", "<pad>", "4"]);
+        assert_eq!(sequence.offsets, vec![(0, 0), (0, 0), (4, 5)]);
+        assert_eq!(sequence.special_tokens_mask, vec![1, 1, 0]);
+    }
+}
@@ -1,4 +1,11 @@
+pub mod encoding;
 pub mod special_tokens;
+pub mod template;
 pub mod token;
+pub mod normalizers;
 pub mod pre_tokenizers;
+pub mod rules;
+pub mod sentences;
 pub mod tokenizers;
+pub mod vocabulary;
+pub mod word_counts;
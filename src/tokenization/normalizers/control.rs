@@ -0,0 +1,26 @@
+use crate::tokenization::normalizers::normalizer::{NormalizedChar, Normalizer};
+
+pub struct RemoveControl;
+
+impl Normalizer for RemoveControl {
+    // whitespace control characters such as '\n' or '\t' are kept
+    fn normalize(&self, chars: Vec<NormalizedChar>) -> Vec<NormalizedChar> {
+        chars.into_iter().filter(|(c, _)| !c.is_control() || c.is_whitespace()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn control_characters_are_removed() {
+        let chars: Vec<NormalizedChar> = "a\u{0}\tb\u{7f}"
+            .char_indices()
+            .map(|(i, c)| (c, (i, i + 1, i, i + 1)))
+            .collect();
+
+        let result: String = RemoveControl.normalize(chars).iter().map(|c| c.0).collect();
+        assert_eq!(result, "a\tb");
+    }
+}
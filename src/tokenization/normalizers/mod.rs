@@ -0,0 +1,51 @@
+use pyo3::pyclass;
+use serde::{Deserialize, Serialize};
+
+use self::normalizer::Normalizer;
+use self::unicode::{Form, Unicode};
+use crate::tokenization::token::Token;
+
+pub mod normalizer;
+pub mod control;
+pub mod strip_accents;
+pub mod unicode;
+pub mod whitespace;
+
+// the Unicode forms keep their usual names in Python and in saved pipelines
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Deserialize, Serialize)]
+#[pyclass]
+pub enum NormalizerKind {
+    NFC,
+    NFD,
+    NFKC,
+    NFKD,
+    StripAccents,
+    RemoveControl,
+    CollapseWhiteSpace,
+}
+
+impl NormalizerKind {
+    pub fn get_processor(&self) -> &dyn Normalizer {
+        match self {
+            NormalizerKind::NFC => &Unicode(Form::Nfc),
+            NormalizerKind::NFD => &Unicode(Form::Nfd),
+            NormalizerKind::NFKC => &Unicode(Form::Nfkc),
+            NormalizerKind::NFKD => &Unicode(Form::Nfkd),
+            NormalizerKind::StripAccents => &strip_accents::StripAccents,
+            NormalizerKind::RemoveControl => &control::RemoveControl,
+            NormalizerKind::CollapseWhiteSpace => &whitespace::CollapseWhiteSpace,
+        }
+    }
+}
+
+// run the word of a token through every normalizer, in order, keeping track
+// of where each character comes from
+pub fn normalize(normalizers: &[NormalizerKind], token: &Token) -> Token {
+    let mut chars = token.aligned_chars();
+    for normalizer in normalizers.iter() {
+        chars = normalizer.get_processor().normalize(chars);
+    }
+
+    token.normalized(chars)
+}
@@ -0,0 +1,15 @@
+use crate::tokenization::token::Alignment;
+
+// a character of the normalized text and the span of the original text it comes from
+pub type NormalizedChar = (char, Alignment);
+
+pub trait Normalizer {
+    fn normalize(&self, chars: Vec<NormalizedChar>) -> Vec<NormalizedChar>;
+}
+
+// smallest span covering all the given ones
+pub fn merge_alignments(chars: &[NormalizedChar]) -> Alignment {
+    chars.iter().fold((usize::MAX, 0, usize::MAX, 0), |span, (_, a)| {
+        (span.0.min(a.0), span.1.max(a.1), span.2.min(a.2), span.3.max(a.3))
+    })
+}
@@ -0,0 +1,34 @@
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+use crate::tokenization::normalizers::normalizer::{NormalizedChar, Normalizer};
+
+pub struct StripAccents;
+
+impl Normalizer for StripAccents {
+    // decompose every character and drop the combining marks
+    fn normalize(&self, chars: Vec<NormalizedChar>) -> Vec<NormalizedChar> {
+        chars
+            .into_iter()
+            .flat_map(|(c, alignment)| {
+                std::iter::once(c)
+                    .nfd()
+                    .filter(|d| !is_combining_mark(*d))
+                    .map(move |d| (d, alignment))
+                    .collect::<Vec<NormalizedChar>>()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accents_are_removed() {
+        let chars: Vec<NormalizedChar> = vec![('é', (0, 2, 0, 1)), ('e', (2, 3, 1, 2)), ('\u{301}', (3, 5, 2, 3))];
+
+        let result = StripAccents.normalize(chars);
+        assert_eq!(result, vec![('e', (0, 2, 0, 1)), ('e', (2, 3, 1, 2))]);
+    }
+}
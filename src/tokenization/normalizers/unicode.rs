@@ -0,0 +1,81 @@
+use unicode_normalization::{char::canonical_combining_class, UnicodeNormalization};
+
+use crate::tokenization::normalizers::normalizer::{merge_alignments, NormalizedChar, Normalizer};
+
+pub enum Form {
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+pub struct Unicode(pub Form);
+
+impl Normalizer for Unicode {
+    // characters are normalized by clusters, a starter and the combining marks
+    // that follow it. When a cluster changes, all its characters are aligned on
+    // the whole cluster.
+    fn normalize(&self, chars: Vec<NormalizedChar>) -> Vec<NormalizedChar> {
+        let mut normalized: Vec<NormalizedChar> = Vec::new();
+        let mut start = 0;
+        while start < chars.len() {
+            let mut end = start + 1;
+            while end < chars.len() && canonical_combining_class(chars[end].0) != 0 {
+                end += 1;
+            }
+
+            let cluster: String = chars[start..end].iter().map(|c| c.0).collect();
+            let form: String = match self.0 {
+                Form::Nfc => cluster.nfc().collect(),
+                Form::Nfd => cluster.nfd().collect(),
+                Form::Nfkc => cluster.nfkc().collect(),
+                Form::Nfkd => cluster.nfkd().collect(),
+            };
+
+            if form == cluster {
+                normalized.extend_from_slice(&chars[start..end]);
+            } else {
+                let span = merge_alignments(&chars[start..end]);
+                normalized.extend(form.chars().map(|c| (c, span)));
+            }
+            start = end;
+        }
+
+        normalized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(text: &str) -> Vec<NormalizedChar> {
+        text.char_indices().enumerate().map(|(n, (i, c))| (c, (i, i + c.len_utf8(), n, n + 1))).collect()
+    }
+
+    fn text(chars: &[NormalizedChar]) -> String {
+        chars.iter().map(|c| c.0).collect()
+    }
+
+    #[test]
+    fn composition() {
+        let result = Unicode(Form::Nfc).normalize(chars("e\u{301}t\u{e9}"));
+        assert_eq!(text(&result), "\u{e9}t\u{e9}");
+        assert_eq!(result[0].1, (0, 3, 0, 2));
+        assert_eq!(result[2].1, (4, 6, 3, 4));
+    }
+
+    #[test]
+    fn decomposition() {
+        let result = Unicode(Form::Nfd).normalize(chars("\u{e9}"));
+        assert_eq!(text(&result), "e\u{301}");
+        assert_eq!(result[1].1, (0, 2, 0, 1));
+    }
+
+    #[test]
+    fn compatibility() {
+        let result = Unicode(Form::Nfkc).normalize(chars("ＡＢ\u{fb01}"));
+        assert_eq!(text(&result), "ABfi");
+        assert_eq!(result[3].1, (6, 9, 2, 3));
+    }
+}
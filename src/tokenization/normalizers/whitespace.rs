@@ -0,0 +1,44 @@
+use crate::tokenization::normalizers::normalizer::{merge_alignments, NormalizedChar, Normalizer};
+
+pub struct CollapseWhiteSpace;
+
+impl Normalizer for CollapseWhiteSpace {
+    // every run of whitespace becomes a single space
+    fn normalize(&self, chars: Vec<NormalizedChar>) -> Vec<NormalizedChar> {
+        let mut normalized: Vec<NormalizedChar> = Vec::new();
+        let mut start = 0;
+        while start < chars.len() {
+            if !chars[start].0.is_whitespace() {
+                normalized.push(chars[start]);
+                start += 1;
+                continue;
+            }
+
+            let mut end = start + 1;
+            while end < chars.len() && chars[end].0.is_whitespace() {
+                end += 1;
+            }
+            normalized.push((' ', merge_alignments(&chars[start..end])));
+            start = end;
+        }
+
+        normalized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whitespace_runs_are_collapsed() {
+        let chars: Vec<NormalizedChar> = "a \n\tb"
+            .char_indices()
+            .map(|(i, c)| (c, (i, i + 1, i, i + 1)))
+            .collect();
+
+        let result = CollapseWhiteSpace.normalize(chars);
+        assert_eq!(result.iter().map(|c| c.0).collect::<String>(), "a b");
+        assert_eq!(result[1].1, (1, 4, 1, 4));
+    }
+}
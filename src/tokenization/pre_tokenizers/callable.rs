@@ -0,0 +1,116 @@
+use std::{fmt, sync::Arc};
+
+use pyo3::{PyObject, Python};
+
+use crate::tokenization::token::{Token, Word};
+use crate::utils::errors::{Error, Result};
+
+type Function = dyn Fn(Vec<&str>) -> Result<Vec<Word>> + Send + Sync;
+
+// a function given the words of the tokens between two special tokens, and
+// returning the new words. Their offsets are found by looking for them, in
+// order, in the words they come from.
+#[derive(Clone)]
+pub struct Callable {
+    function: Arc<Function>,
+}
+
+impl fmt::Debug for Callable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Callable")
+    }
+}
+
+impl Callable {
+    pub fn new<F: Fn(Vec<&str>) -> Result<Vec<Word>> + Send + Sync + 'static>(function: F) -> Self {
+        Callable { function: Arc::new(function) }
+    }
+
+    // the GIL is taken for every call, what the function raises is kept as is
+    pub fn from_python(function: PyObject) -> Self {
+        Callable::new(move |words| {
+            Python::with_gil(|py| {
+                let result = function.call1(py, (words,)).map_err(|e| Error::Python(Box::new(e)))?;
+                result.extract(py).map_err(|e| {
+                    Error::Invalid(format!("a callable pre-tokenizer must return a list of strings: {}", e))
+                })
+            })
+        })
+    }
+
+    // special tokens are never given to the function
+    pub fn pre_tokenize(&self, tokens: Vec<Token>) -> Result<Vec<Token>> {
+        let mut new_tokens: Vec<Token> = Vec::with_capacity(tokens.len());
+        let mut run: Vec<Token> = Vec::new();
+        for token in tokens {
+            if token.special {
+                new_tokens.extend(self.call(&run)?);
+                new_tokens.push(token);
+                run.clear();
+            } else {
+                run.push(token);
+            }
+        }
+        new_tokens.extend(self.call(&run)?);
+
+        Ok(new_tokens)
+    }
+
+    fn call(&self, tokens: &[Token]) -> Result<Vec<Token>> {
+        if tokens.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let words: Vec<Word> = (self.function)(tokens.iter().map(|t| t.word.as_str()).collect())?;
+        Ok(align(tokens, words))
+    }
+}
+
+// a word that cannot be found (e.g. it was lowercased) spans what is left of
+// the token it is in
+fn align(tokens: &[Token], words: Vec<Word>) -> Vec<Token> {
+    let (mut i, mut cursor) = (0, 0);
+    let mut aligned: Vec<Token> = Vec::with_capacity(words.len());
+    for word in words {
+        let found = (i..tokens.len()).find_map(|j| {
+            let from = if j == i { cursor } else { 0 };
+            tokens[j].word[from..].find(&word).map(|position| (j, from + position))
+        });
+
+        let start = match found {
+            Some((j, start)) => {
+                i = j;
+                cursor = start + word.len();
+                start
+            },
+            None => {
+                if cursor == tokens[i].word.len() && i + 1 < tokens.len() {
+                    i += 1;
+                    cursor = 0;
+                }
+                let start = cursor;
+                cursor = tokens[i].word.len();
+                start
+            }
+        };
+        aligned.push(tokens[i].sub(word, start, cursor));
+    }
+
+    aligned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligned_words() {
+        let tokens = Token::new("Hello World").split(vec![("Hello".to_string(), 0, 5), ("World".to_string(), 6, 11)]);
+
+        let words: Vec<Word> = ["He", "llo", "world", "!"].iter().map(|w| w.to_string()).collect();
+        let aligned = align(&tokens, words);
+
+        let offsets: Vec<(&str, usize, usize)> = aligned.iter().map(|t| (t.word.as_str(), t.start, t.end)).collect();
+        assert_eq!(offsets, vec![("He", 0, 2), ("llo", 2, 5), ("world", 6, 11), ("!", 11, 11)]);
+    }
+}
@@ -1,27 +1,248 @@
-use pyo3::pyclass;
+use pyo3::{pyclass, pymethods, types::PyAny, PyResult};
+use serde::{ser, Deserialize, Serialize, Serializer};
 
 use self::pre_tokenizer::PreTokenizer;
+use crate::tokenization::{rules::{Language, RuleBased}, token::{Token, Word}};
+use crate::utils::errors::{Error, Result};
 
 pub mod pre_tokenizer;
+pub mod callable;
 pub mod case_fold;
 pub mod punctuation;
+pub mod script;
+pub mod social;
+pub mod split;
+pub mod stem;
+pub mod unicode;
 pub mod whitespace;
 mod utils;
 
-#[derive(Clone)]
+// what becomes of the pieces matched by a splitting pattern
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[pyclass]
-pub enum PreTokenizerKind {
+pub enum SplitBehavior {
+    Removed,
+    Isolated,
+    MergedWithPrevious,
+    MergedWithNext,
+}
+
+#[derive(Clone, Debug)]
+enum Kind {
+    CaseFold,
+    Punctuation,
+    WhiteSpace,
+    UnicodePunctuation,
+    Digits,
+    ScriptBoundary,
+    Cjk,
+    Regex(split::Split),
+    Rules(RuleBased),
+    Social(social::Social),
+    Stem(stem::Stem),
+    Callable(callable::Callable),
+}
+
+// not a plain enum since pyo3 enums cannot hold data: the pre-tokenizers
+// without options are class attributes, the other ones are built with
+// static methods ('PreTokenizerKind.Regex(pattern, behavior)',
+// 'PreTokenizerKind.Rules(Language.French)')
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "Spec")]
+#[pyclass]
+pub struct PreTokenizerKind {
+    kind: Kind,
+}
+
+// how a pre-tokenizer is saved, the compiled ones are rebuilt from their options
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "type")]
+enum Spec {
     CaseFold,
     Punctuation,
     WhiteSpace,
+    UnicodePunctuation,
+    Digits,
+    ScriptBoundary,
+    #[serde(rename = "CJK")]
+    Cjk,
+    Regex { pattern: Word, behavior: SplitBehavior },
+    Rules { language: Language },
+    Social { reduce_len: bool, handle_placeholder: Option<Word> },
+    Stem { language: stem::StemLanguage },
+}
+
+impl TryFrom<&PreTokenizerKind> for Spec {
+    type Error = Error;
+
+    fn try_from(pre_tokenizer: &PreTokenizerKind) -> Result<Self> {
+        Ok(match &pre_tokenizer.kind {
+            Kind::CaseFold => Spec::CaseFold,
+            Kind::Punctuation => Spec::Punctuation,
+            Kind::WhiteSpace => Spec::WhiteSpace,
+            Kind::UnicodePunctuation => Spec::UnicodePunctuation,
+            Kind::Digits => Spec::Digits,
+            Kind::ScriptBoundary => Spec::ScriptBoundary,
+            Kind::Cjk => Spec::Cjk,
+            Kind::Regex(split) => Spec::Regex { pattern: split.pattern().to_string(), behavior: split.behavior().clone() },
+            Kind::Rules(rules) => Spec::Rules { language: rules.language().clone() },
+            Kind::Social(social) => Spec::Social {
+                reduce_len: social.reduce_len(),
+                handle_placeholder: social.handle_placeholder().cloned(),
+            },
+            Kind::Stem(stem) => Spec::Stem { language: stem.language().clone() },
+            Kind::Callable(_) => return Err(Error::Invalid("callable pre-tokenizers cannot be saved".to_string())),
+        })
+    }
+}
+
+impl Serialize for PreTokenizerKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        Spec::try_from(self).map_err(ser::Error::custom)?.serialize(serializer)
+    }
+}
+
+impl TryFrom<Spec> for PreTokenizerKind {
+    type Error = Error;
+
+    fn try_from(spec: Spec) -> Result<Self> {
+        Ok(match spec {
+            Spec::CaseFold => PreTokenizerKind::CaseFold,
+            Spec::Punctuation => PreTokenizerKind::Punctuation,
+            Spec::WhiteSpace => PreTokenizerKind::WhiteSpace,
+            Spec::UnicodePunctuation => PreTokenizerKind::UnicodePunctuation,
+            Spec::Digits => PreTokenizerKind::Digits,
+            Spec::ScriptBoundary => PreTokenizerKind::ScriptBoundary,
+            Spec::Cjk => PreTokenizerKind::CJK,
+            Spec::Regex { pattern, behavior } => PreTokenizerKind::regex_(&pattern, behavior)?,
+            Spec::Rules { language } => PreTokenizerKind::rules(language),
+            Spec::Social { reduce_len, handle_placeholder } => PreTokenizerKind::social(reduce_len, handle_placeholder),
+            Spec::Stem { language } => PreTokenizerKind::stem(language),
+        })
+    }
 }
 
+#[allow(non_upper_case_globals)]
+#[pymethods]
 impl PreTokenizerKind {
-    pub fn get_processor(&self) -> &dyn PreTokenizer {
-        match self {
-            PreTokenizerKind::CaseFold => &case_fold::CaseFold,
-            PreTokenizerKind::Punctuation => &punctuation::Punctuation,
-            PreTokenizerKind::WhiteSpace => &whitespace::WhiteSpace,
+    #[classattr]
+    pub const CaseFold: PreTokenizerKind = PreTokenizerKind { kind: Kind::CaseFold };
+    #[classattr]
+    pub const Punctuation: PreTokenizerKind = PreTokenizerKind { kind: Kind::Punctuation };
+    #[classattr]
+    pub const WhiteSpace: PreTokenizerKind = PreTokenizerKind { kind: Kind::WhiteSpace };
+    #[classattr]
+    pub const UnicodePunctuation: PreTokenizerKind = PreTokenizerKind { kind: Kind::UnicodePunctuation };
+    #[classattr]
+    pub const Digits: PreTokenizerKind = PreTokenizerKind { kind: Kind::Digits };
+    #[classattr]
+    pub const ScriptBoundary: PreTokenizerKind = PreTokenizerKind { kind: Kind::ScriptBoundary };
+    #[classattr]
+    pub const CJK: PreTokenizerKind = PreTokenizerKind { kind: Kind::Cjk };
+
+    #[staticmethod]
+    #[pyo3(name = "Regex")]
+    #[args(behavior = "SplitBehavior::Isolated")]
+    fn regex(pattern: &str, behavior: SplitBehavior) -> PyResult<Self> {
+        Ok(PreTokenizerKind::regex_(pattern, behavior)?)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "Rules")]
+    pub fn rules(language: Language) -> Self {
+        PreTokenizerKind { kind: Kind::Rules(RuleBased::new(&language)) }
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "Social")]
+    #[args(reduce_len = "false", handle_placeholder = "None")]
+    pub fn social(reduce_len: bool, handle_placeholder: Option<Word>) -> Self {
+        PreTokenizerKind { kind: Kind::Social(social::Social::new(reduce_len, handle_placeholder)) }
+    }
+
+    // words are expected to be lowercased, put 'CaseFold' first
+    #[staticmethod]
+    #[pyo3(name = "Stem")]
+    pub fn stem(language: stem::StemLanguage) -> Self {
+        PreTokenizerKind { kind: Kind::Stem(stem::Stem::new(&language)) }
+    }
+
+    // 'function' gets a list of words and returns a list of words, what it
+    // raises is raised by the tokenizer
+    #[staticmethod]
+    #[pyo3(name = "Callable")]
+    fn callable(function: &PyAny) -> PyResult<Self> {
+        if !function.is_callable() {
+            return Err(Error::Invalid("the pre-tokenizer must be callable".to_string()).into());
         }
+        Ok(PreTokenizerKind { kind: Kind::Callable(callable::Callable::from_python(function.into())) })
+    }
+}
+
+impl PreTokenizerKind {
+    pub fn regex_(pattern: &str, behavior: SplitBehavior) -> Result<Self> {
+        Ok(PreTokenizerKind { kind: Kind::Regex(split::Split::new(pattern, behavior)?) })
+    }
+
+    pub fn callable_<F: Fn(Vec<&str>) -> Result<Vec<Word>> + Send + Sync + 'static>(function: F) -> Self {
+        PreTokenizerKind { kind: Kind::Callable(callable::Callable::new(function)) }
+    }
+
+    // special tokens are left untouched, the other ones are pre-tokenized one by one
+    pub fn pre_tokenize_(&self, tokens: Vec<Token>) -> Result<Vec<Token>> {
+        let processor: &dyn PreTokenizer = match &self.kind {
+            Kind::Callable(callable) => return callable.pre_tokenize(tokens),
+            Kind::CaseFold => &case_fold::CaseFold,
+            Kind::Punctuation => &punctuation::Punctuation,
+            Kind::WhiteSpace => &whitespace::WhiteSpace,
+            Kind::UnicodePunctuation => &unicode::UnicodePunctuation,
+            Kind::Digits => &unicode::Digits,
+            Kind::ScriptBoundary => &script::ScriptBoundary,
+            Kind::Cjk => &unicode::Cjk,
+            Kind::Regex(split) => split,
+            Kind::Rules(rules) => rules,
+            Kind::Social(social) => social,
+            Kind::Stem(stem) => stem,
+        };
+
+        // special tokens are kept as is, each run of tokens between them is
+        // given in one go
+        let mut new_tokens: Vec<Token> = Vec::with_capacity(tokens.len());
+        let mut run: Vec<Token> = Vec::new();
+        for token in tokens {
+            if token.special {
+                if !run.is_empty() {
+                    new_tokens.extend(processor.pre_tokenize(&run));
+                    run.clear();
+                }
+                new_tokens.push(token);
+            } else {
+                run.push(token);
+            }
+        }
+        if !run.is_empty() {
+            new_tokens.extend(processor.pre_tokenize(&run));
+        }
+
+        Ok(new_tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn special_tokens_are_skipped() {
+        let mut tokens = Token::new("Hello world [SEP] again").split(vec![
+            ("Hello world".to_string(), 0, 11),
+            ("[SEP]".to_string(), 12, 17),
+            ("again".to_string(), 18, 23),
+        ]);
+        tokens[1].special = true;
+
+        let pre_tokenizer = PreTokenizerKind { kind: Kind::WhiteSpace };
+        let words: Vec<Word> = pre_tokenizer.pre_tokenize_(tokens).unwrap().into_iter().map(|t| t.word).collect();
+        assert_eq!(words, vec!["Hello", "world", "[SEP]", "again"]);
     }
 }
@@ -1,5 +1,5 @@
 use crate::tokenization::token::Token;
 
 pub trait PreTokenizer {
-    fn pre_tokenize(&self, tokens: &Vec<Token>) -> Vec<Token>;
+    fn pre_tokenize(&self, tokens: &[Token]) -> Vec<Token>;
 }
@@ -1,17 +1,21 @@
+use std::sync::OnceLock;
+
 use regex::Regex;
 
 use crate::tokenization::token::Token;
 use crate::tokenization::pre_tokenizers::pre_tokenizer::PreTokenizer;
-use crate::tokenization::pre_tokenizers::utils::split_token;
+use crate::tokenization::pre_tokenizers::{utils::split_token, SplitBehavior};
 
 pub struct Punctuation;
 
 impl PreTokenizer for Punctuation {
-    fn pre_tokenize(&self, tokens: &Vec<Token>) -> Vec<Token> {
+    fn pre_tokenize(&self, tokens: &[Token]) -> Vec<Token> {
+        static PUNCTUATION: OnceLock<Regex> = OnceLock::new();
+        let re = PUNCTUATION.get_or_init(|| Regex::new(r"[.,:;\-!?']+").unwrap());
+
         let mut new_tokens: Vec<Token> = Vec::new();
         for token in tokens.iter() {
-            let re = Regex::new(r"[.,:;\-!?']+").unwrap();
-            new_tokens.extend(split_token(token, &re, true));
+            new_tokens.extend(split_token(token, re, &SplitBehavior::Isolated));
         }
 
         new_tokens
@@ -26,9 +30,10 @@ mod tests {
     fn tokenization_exclamation() {
         let punctuation: Punctuation = Punctuation{};
 
-        let input: Vec<Token> = vec![Token{word: "Hello!".to_string()}];
+        let input: Vec<Token> = vec![Token::new("Hello!")];
         let expected: Vec<Token> = vec![
-            Token{word: "Hello".to_string()}, Token{word: "!".to_string()}
+            Token{word: "Hello".to_string(), start: 0, end: 5, char_start: 0, char_end: 5, ..Default::default()},
+            Token{word: "!".to_string(), start: 5, end: 6, char_start: 5, char_end: 6, ..Default::default()}
         ];
 
         let result: Vec<Token> = punctuation.pre_tokenize(&input);
@@ -39,9 +44,10 @@ mod tests {
     fn tokenization_ellipsis() {
         let punctuation: Punctuation = Punctuation{};
 
-        let input: Vec<Token> = vec![Token{word: "Hello...".to_string()}];
+        let input: Vec<Token> = vec![Token::new("Hello...")];
         let expected: Vec<Token> = vec![
-            Token{word: "Hello".to_string()}, Token{word: "...".to_string()}
+            Token{word: "Hello".to_string(), start: 0, end: 5, char_start: 0, char_end: 5, ..Default::default()},
+            Token{word: "...".to_string(), start: 5, end: 8, char_start: 5, char_end: 8, ..Default::default()}
         ];
 
         let result: Vec<Token> = punctuation.pre_tokenize(&input);
@@ -52,11 +58,11 @@ mod tests {
     fn tokenization_between_words() {
         let punctuation: Punctuation = Punctuation{};
 
-        let input: Vec<Token> = vec![Token{word: "Hello,Lucas".to_string()}];
+        let input: Vec<Token> = vec![Token::new("Héllo,Lucas")];
         let expected: Vec<Token> = vec![
-            Token{word: "Hello".to_string()},
-            Token{word: ",".to_string()},
-            Token{word: "Lucas".to_string()}
+            Token{word: "Héllo".to_string(), start: 0, end: 6, char_start: 0, char_end: 5, ..Default::default()},
+            Token{word: ",".to_string(), start: 6, end: 7, char_start: 5, char_end: 6, ..Default::default()},
+            Token{word: "Lucas".to_string(), start: 7, end: 12, char_start: 6, char_end: 11, ..Default::default()}
         ];
 
         let result: Vec<Token> = punctuation.pre_tokenize(&input);
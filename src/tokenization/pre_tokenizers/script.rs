@@ -0,0 +1,51 @@
+use unicode_script::{Script, UnicodeScript};
+
+use crate::tokenization::token::{Token, Word};
+use crate::tokenization::pre_tokenizers::pre_tokenizer::PreTokenizer;
+
+// split where the script changes ('GPU加速' -> 'GPU', '加速'). Characters
+// shared between scripts (digits, punctuation, spaces, combining marks)
+// stay with the text around them.
+pub struct ScriptBoundary;
+
+impl PreTokenizer for ScriptBoundary {
+    fn pre_tokenize(&self, tokens: &[Token]) -> Vec<Token> {
+        let mut new_tokens: Vec<Token> = Vec::new();
+        for token in tokens.iter() {
+            let word: &str = &token.word;
+            let mut pieces: Vec<(Word, usize, usize)> = Vec::new();
+            let mut start = 0;
+            let mut current: Option<Script> = None;
+
+            for (i, c) in word.char_indices() {
+                let script = c.script();
+                if matches!(script, Script::Common | Script::Inherited) {
+                    continue;
+                }
+                if current.is_some_and(|s| s != script) {
+                    pieces.push((word[start..i].to_string(), start, i));
+                    start = i;
+                }
+                current = Some(script);
+            }
+            pieces.push((word[start..].to_string(), start, word.len()));
+
+            new_tokens.extend(token.split(pieces));
+        }
+
+        new_tokens
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_changes() {
+        let result = ScriptBoundary.pre_tokenize(&[Token::new("GPU加速 2x Москва")]);
+        let words: Vec<&str> = result.iter().map(|t| t.word.as_str()).collect();
+        assert_eq!(words, vec!["GPU", "加速 2", "x ", "Москва"]);
+        assert_eq!((result[1].start, result[1].end), (3, 11));
+    }
+}
@@ -0,0 +1,138 @@
+use regex::{Captures, Regex};
+
+use crate::tokenization::token::{Token, Word};
+use crate::tokenization::pre_tokenizers::pre_tokenizer::PreTokenizer;
+
+// the first alternatives win, so urls and e-mails are tried before mentions,
+// numbers before emoticons ('8/10' is not '8/' then '10', which is also why
+// '8' is not used as eyes) and emoticons before punctuation
+const PATTERNS: [(&str, &str); 10] = [
+    ("url", r#"(?:https?://|www\.)[^\s<>"]*[^\s<>".,;:!?)\]'"]"#),
+    ("email", r"[\w.+-]+@[\w-]+(?:\.[\w-]+)+"),
+    ("mention", r"@\w+"),
+    ("hashtag", r"#\w+"),
+    ("number", r"[$€£]?[+-]?\d+(?:[.,:/]\d+)*(?:%|°?[\p{L}µ]+)?"),
+    ("emoticon", r#"[<>]?[:;=][\-o*']?[)\](\[dDpP/\\:}{@|]|[)\](\[dDpP/\\:}{@|][\-o*']?[:;=][<>]?|</?3"#),
+    (
+        "emoji",
+        r"(?:\p{Extended_Pictographic}|\p{Regional_Indicator}{2})[\x{FE0F}\x{20E3}\p{Emoji_Modifier}]*(?:\x{200D}\p{Extended_Pictographic}[\x{FE0F}\p{Emoji_Modifier}]*)*",
+    ),
+    ("word", r"\w+(?:['’\-]\w+)*"),
+    ("ellipsis", r"\.{2,}|…"),
+    ("other", r"[^\w\s]"),
+];
+
+// keeps urls, e-mails, @mentions, #hashtags, emoticons, emoji sequences and
+// numbers with units (':-)', '👍🏽', '3.5GB') as single tokens
+#[derive(Clone, Debug)]
+pub struct Social {
+    re: Regex,
+    // 'soooooo' -> 'sooo'
+    reduce_len: bool,
+    // replaces every @mention
+    handle_placeholder: Option<Word>,
+}
+
+impl Social {
+    pub fn new(reduce_len: bool, handle_placeholder: Option<Word>) -> Self {
+        let pattern = PATTERNS
+            .iter()
+            .map(|(name, pattern)| format!("(?P<{}>{})", name, pattern))
+            .collect::<Vec<String>>()
+            .join("|");
+
+        Social { re: Regex::new(&pattern).unwrap(), reduce_len, handle_placeholder }
+    }
+
+    pub fn reduce_len(&self) -> bool {
+        self.reduce_len
+    }
+
+    pub fn handle_placeholder(&self) -> Option<&Word> {
+        self.handle_placeholder.as_ref()
+    }
+
+    fn word(&self, captures: &Captures) -> Word {
+        let text = captures.get(0).unwrap().as_str();
+        if captures.name("mention").is_some() {
+            if let Some(placeholder) = &self.handle_placeholder {
+                return placeholder.to_owned();
+            }
+        }
+        if self.reduce_len && captures.name("word").is_some() {
+            return reduce_length(text);
+        }
+        text.to_string()
+    }
+}
+
+// characters repeated more than three times in a row are kept three times
+fn reduce_length(word: &str) -> Word {
+    let mut reduced = String::new();
+    let mut previous: Option<char> = None;
+    let mut repeats = 0;
+    for c in word.chars() {
+        repeats = if previous == Some(c) { repeats + 1 } else { 1 };
+        if repeats <= 3 {
+            reduced.push(c);
+        }
+        previous = Some(c);
+    }
+
+    reduced
+}
+
+impl PreTokenizer for Social {
+    fn pre_tokenize(&self, tokens: &[Token]) -> Vec<Token> {
+        let mut new_tokens: Vec<Token> = Vec::new();
+        for token in tokens.iter() {
+            let pieces: Vec<(Word, usize, usize)> = self.re
+                .captures_iter(&token.word)
+                .map(|captures| {
+                    let m = captures.get(0).unwrap();
+                    (self.word(&captures), m.start(), m.end())
+                })
+                .collect();
+            new_tokens.extend(token.split(pieces));
+        }
+
+        new_tokens
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(social: &Social, text: &str) -> Vec<Word> {
+        social.pre_tokenize(&[Token::new(text)]).into_iter().map(|t| t.word).collect()
+    }
+
+    #[test]
+    fn social_tokens() {
+        let social = Social::new(false, None);
+        assert_eq!(
+            words(&social, "@bob see https://x.co/a?b=1, mail me@x.com :-) #fail 3.5GB 👍🏽👨‍👩‍👧 🇫🇷!!"),
+            vec![
+                "@bob", "see", "https://x.co/a?b=1", ",", "mail", "me@x.com", ":-)", "#fail", "3.5GB",
+                "👍🏽", "👨‍👩‍👧", "🇫🇷", "!", "!"
+            ]
+        );
+    }
+
+    #[test]
+    fn numbers_are_not_emoticons() {
+        let social = Social::new(false, None);
+        assert_eq!(words(&social, "8/10 at 8:30 (8) ;-)"), vec!["8/10", "at", "8:30", "(", "8", ")", ";-)"]);
+    }
+
+    #[test]
+    fn options() {
+        let social = Social::new(true, Some("@USER".to_string()));
+        let result = social.pre_tokenize(&[Token::new("@alice soooooo coool")]);
+        let words: Vec<&str> = result.iter().map(|t| t.word.as_str()).collect();
+        assert_eq!(words, vec!["@USER", "sooo", "coool"]);
+        assert_eq!((result[0].start, result[0].end), (0, 6));
+        assert_eq!((result[1].start, result[1].end), (7, 14));
+    }
+}
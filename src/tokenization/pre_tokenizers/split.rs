@@ -0,0 +1,63 @@
+use regex::Regex;
+
+use crate::tokenization::token::Token;
+use crate::tokenization::pre_tokenizers::{pre_tokenizer::PreTokenizer, utils::split_token, SplitBehavior};
+use crate::utils::errors::{Error, Result};
+
+// split on a user-defined pattern, what happens to the matches depends on
+// the behavior
+#[derive(Clone, Debug)]
+pub struct Split {
+    re: Regex,
+    behavior: SplitBehavior,
+}
+
+impl Split {
+    pub fn new(pattern: &str, behavior: SplitBehavior) -> Result<Self> {
+        let re = Regex::new(pattern)
+            .map_err(|e| Error::Invalid(format!("invalid pattern '{}': {}", pattern, e)))?;
+        Ok(Split { re, behavior })
+    }
+
+    pub fn pattern(&self) -> &str {
+        self.re.as_str()
+    }
+
+    pub fn behavior(&self) -> &SplitBehavior {
+        &self.behavior
+    }
+}
+
+impl PreTokenizer for Split {
+    fn pre_tokenize(&self, tokens: &[Token]) -> Vec<Token> {
+        let mut new_tokens: Vec<Token> = Vec::new();
+        for token in tokens.iter() {
+            new_tokens.extend(split_token(token, &self.re, &self.behavior));
+        }
+
+        new_tokens
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gpt2_pattern() {
+        let split = Split::new(
+            r"'s|'t|'re|'ve|'m|'ll|'d| ?\p{L}+| ?\p{N}+| ?[^\s\p{L}\p{N}]+|\s+",
+            SplitBehavior::Isolated
+        ).unwrap();
+
+        let result: Vec<Token> = split.pre_tokenize(&[Token::new("It's 42 cats!")]);
+        let words: Vec<&str> = result.iter().map(|t| t.word.as_str()).collect();
+        assert_eq!(words, vec!["It", "'s", " 42", " cats", "!"]);
+        assert_eq!((result[3].start, result[3].end), (7, 12));
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        assert!(matches!(Split::new("(unclosed", SplitBehavior::Removed), Err(Error::Invalid(_))));
+    }
+}
@@ -0,0 +1,105 @@
+use std::sync::OnceLock;
+
+use pyo3::pyclass;
+use rust_stemmers::{Algorithm, Stemmer};
+use serde::{Deserialize, Serialize};
+
+use crate::tokenization::token::Token;
+use crate::tokenization::pre_tokenizers::pre_tokenizer::PreTokenizer;
+
+// languages with a Snowball stemmer, they are not the ones of the rule-based
+// tokenizers
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[pyclass]
+pub enum StemLanguage {
+    English,
+    French,
+    Italian,
+}
+
+// stemmers do not keep any state, a single one is built per language
+fn stemmer(language: &StemLanguage) -> &'static Stemmer {
+    static ENGLISH: OnceLock<Stemmer> = OnceLock::new();
+    static FRENCH: OnceLock<Stemmer> = OnceLock::new();
+    static ITALIAN: OnceLock<Stemmer> = OnceLock::new();
+
+    let (stemmer, algorithm) = match language {
+        StemLanguage::English => (&ENGLISH, Algorithm::English),
+        StemLanguage::French => (&FRENCH, Algorithm::French),
+        StemLanguage::Italian => (&ITALIAN, Algorithm::Italian),
+    };
+    stemmer.get_or_init(|| Stemmer::create(algorithm))
+}
+
+// replaces words with their Snowball stem (Porter2 for English): 'connections'
+// -> 'connect'. Words are expected to be lowercased (see 'CaseFold'), the
+// word before stemming is kept as the surface of the token.
+#[derive(Clone, Debug)]
+pub struct Stem {
+    language: StemLanguage,
+}
+
+impl Stem {
+    pub fn new(language: &StemLanguage) -> Self {
+        Stem { language: language.clone() }
+    }
+
+    pub fn language(&self) -> &StemLanguage {
+        &self.language
+    }
+}
+
+impl PreTokenizer for Stem {
+    fn pre_tokenize(&self, tokens: &[Token]) -> Vec<Token> {
+        let stemmer = stemmer(&self.language);
+
+        tokens
+            .iter()
+            .map(|token| {
+                let stem = stemmer.stem(&token.word);
+                if stem == token.word {
+                    return token.clone();
+                }
+                let mut stemmed = token.with_word(stem.into_owned());
+                stemmed.surface = Some(token.surface.as_ref().unwrap_or(&token.word).to_string());
+                stemmed
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenization::pre_tokenizers::PreTokenizerKind;
+
+    fn stems(language: StemLanguage, words: &[&str]) -> Vec<String> {
+        let tokens: Vec<Token> = words.iter().map(|w| Token::new(w)).collect();
+        Stem::new(&language).pre_tokenize(&tokens).into_iter().map(|t| t.word).collect()
+    }
+
+    #[test]
+    fn languages() {
+        assert_eq!(stems(StemLanguage::English, &["connections", "running", "generously"]), vec!["connect", "run", "generous"]);
+        assert_eq!(stems(StemLanguage::French, &["continuellement", "chevaux"]), vec!["continuel", "cheval"]);
+        assert_eq!(stems(StemLanguage::Italian, &["abbandonata", "gattini"]), vec!["abbandon", "gattin"]);
+    }
+
+    #[test]
+    fn surface_is_kept() {
+        let mut token = Token::new("I was running");
+        token = token.sub("running".to_string(), 6, 13);
+
+        let stemmed = Stem::new(&StemLanguage::English).pre_tokenize(&[token]);
+        assert_eq!(stemmed[0].word, "run");
+        assert_eq!(stemmed[0].surface, Some("running".to_string()));
+        assert_eq!((stemmed[0].start, stemmed[0].end), (6, 13));
+    }
+
+    #[test]
+    fn saved_language() {
+        let json = serde_json::to_string(&PreTokenizerKind::stem(StemLanguage::Italian)).unwrap();
+        assert_eq!(json, r#"{"type":"Stem","language":"Italian"}"#);
+        assert!(serde_json::from_str::<PreTokenizerKind>(&json).is_ok());
+    }
+}
@@ -0,0 +1,75 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::tokenization::token::Token;
+use crate::tokenization::pre_tokenizers::{pre_tokenizer::PreTokenizer, utils::split_token, SplitBehavior};
+
+fn split_all(tokens: &[Token], re: &Regex) -> Vec<Token> {
+    let mut new_tokens: Vec<Token> = Vec::new();
+    for token in tokens.iter() {
+        new_tokens.extend(split_token(token, re, &SplitBehavior::Isolated));
+    }
+
+    new_tokens
+}
+
+// any run of punctuation or symbols ('«', '—', '…', '¿', '。', '$', ...)
+pub struct UnicodePunctuation;
+
+impl PreTokenizer for UnicodePunctuation {
+    fn pre_tokenize(&self, tokens: &[Token]) -> Vec<Token> {
+        static PUNCTUATION: OnceLock<Regex> = OnceLock::new();
+        split_all(tokens, PUNCTUATION.get_or_init(|| Regex::new(r"[\p{P}\p{S}]+").unwrap()))
+    }
+}
+
+// every digit becomes its own token
+pub struct Digits;
+
+impl PreTokenizer for Digits {
+    fn pre_tokenize(&self, tokens: &[Token]) -> Vec<Token> {
+        static DIGITS: OnceLock<Regex> = OnceLock::new();
+        split_all(tokens, DIGITS.get_or_init(|| Regex::new(r"\p{Nd}").unwrap()))
+    }
+}
+
+// every Han character becomes its own token, as in BERT
+pub struct Cjk;
+
+impl PreTokenizer for Cjk {
+    fn pre_tokenize(&self, tokens: &[Token]) -> Vec<Token> {
+        static HAN: OnceLock<Regex> = OnceLock::new();
+        split_all(tokens, HAN.get_or_init(|| Regex::new(r"\p{Han}").unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(pre_tokenizer: &dyn PreTokenizer, text: &str) -> Vec<String> {
+        pre_tokenizer.pre_tokenize(&[Token::new(text)]).into_iter().map(|t| t.word).collect()
+    }
+
+    #[test]
+    fn unicode_punctuation() {
+        assert_eq!(
+            words(&UnicodePunctuation, "«Ciao»—¿qué…"),
+            vec!["«", "Ciao", "»—¿", "qué", "…"]
+        );
+    }
+
+    #[test]
+    fn digits() {
+        assert_eq!(words(&Digits, "in 2023!"), vec!["in ", "2", "0", "2", "3", "!"]);
+    }
+
+    #[test]
+    fn cjk() {
+        let result = Cjk.pre_tokenize(&[Token::new("AI是未来")]);
+        let words: Vec<&str> = result.iter().map(|t| t.word.as_str()).collect();
+        assert_eq!(words, vec!["AI", "是", "未", "来"]);
+        assert_eq!((result[2].start, result[2].char_start), (5, 3));
+    }
+}
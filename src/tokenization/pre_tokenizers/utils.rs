@@ -1,24 +1,85 @@
 use regex::Regex;
 
 use crate::tokenization::token::{Token, Word};
+use crate::tokenization::pre_tokenizers::SplitBehavior;
 
-pub fn split_token(token: &Token, re: &Regex, keep_sep: bool) -> Vec<Token> {
-    let mut words: Vec<Word> = Vec::new();
-    let word: Word = token.word.to_string();
-    let mut cursor = 0;
+// split a token around the matches of 're', the new tokens keep their
+// position in the original text
+pub fn split_token(token: &Token, re: &Regex, behavior: &SplitBehavior) -> Vec<Token> {
+    let word: &str = &token.word;
 
-    for m in re.find_iter(&word) {
+    // (start, end, is a match) for every part of the word, in order
+    let mut parts: Vec<(usize, usize, bool)> = Vec::new();
+    let mut cursor = 0;
+    for m in re.find_iter(word).filter(|m| !m.as_str().is_empty()) {
         if cursor != m.start() {
-            words.push(word[cursor..m.start()].to_string());
-        }
-        if keep_sep {
-            words.push(m.as_str().to_string());
+            parts.push((cursor, m.start(), false));
         }
+        parts.push((m.start(), m.end(), true));
         cursor = m.end();
     }
     if cursor < word.len() {
-        words.push(word[cursor..].to_string())
+        parts.push((cursor, word.len(), false));
+    }
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    // a match waiting for the part that follows it
+    let mut pending: Option<usize> = None;
+    let mut previous_matched = true;
+    for (start, end, matched) in parts {
+        match behavior {
+            SplitBehavior::Removed => if !matched { ranges.push((start, end)) },
+            SplitBehavior::Isolated => ranges.push((start, end)),
+            SplitBehavior::MergedWithPrevious => match ranges.last_mut() {
+                Some(last) if matched && !previous_matched => last.1 = end,
+                _ => ranges.push((start, end)),
+            },
+            SplitBehavior::MergedWithNext => {
+                if let Some(from) = pending.take() {
+                    if matched {
+                        ranges.push((from, start));
+                    } else {
+                        ranges.push((from, end));
+                        continue;
+                    }
+                }
+                if matched {
+                    pending = Some(start);
+                } else {
+                    ranges.push((start, end));
+                }
+            },
+        }
+        previous_matched = matched;
+    }
+    if let Some(from) = pending {
+        ranges.push((from, word.len()));
     }
 
-    words.iter().map(|w| Token{word: w.to_string()}).collect()
+    let pieces: Vec<(Word, usize, usize)> = ranges
+        .into_iter()
+        .map(|(start, end)| (word[start..end].to_string(), start, end))
+        .collect();
+    token.split(pieces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(behavior: SplitBehavior) -> Vec<Word> {
+        let re = Regex::new("-").unwrap();
+        split_token(&Token::new("the-final--countdown"), &re, &behavior)
+            .into_iter()
+            .map(|t| t.word)
+            .collect()
+    }
+
+    #[test]
+    fn behaviors() {
+        assert_eq!(words(SplitBehavior::Removed), vec!["the", "final", "countdown"]);
+        assert_eq!(words(SplitBehavior::Isolated), vec!["the", "-", "final", "-", "-", "countdown"]);
+        assert_eq!(words(SplitBehavior::MergedWithPrevious), vec!["the-", "final-", "-", "countdown"]);
+        assert_eq!(words(SplitBehavior::MergedWithNext), vec!["the", "-final", "-", "-countdown"]);
+    }
 }
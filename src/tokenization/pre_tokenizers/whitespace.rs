@@ -1,17 +1,21 @@
+use std::sync::OnceLock;
+
 use regex::Regex;
 
 use crate::tokenization::token::Token;
 use crate::tokenization::pre_tokenizers::pre_tokenizer::PreTokenizer;
-use crate::tokenization::pre_tokenizers::utils::split_token;
+use crate::tokenization::pre_tokenizers::{utils::split_token, SplitBehavior};
 
 pub struct WhiteSpace;
 
 impl PreTokenizer for WhiteSpace {
-    fn pre_tokenize(&self, tokens: &Vec<Token>) -> Vec<Token> {
+    fn pre_tokenize(&self, tokens: &[Token]) -> Vec<Token> {
+        static WHITESPACE: OnceLock<Regex> = OnceLock::new();
+        let re = WHITESPACE.get_or_init(|| Regex::new(r"\s+").unwrap());
+
         let mut new_tokens: Vec<Token> = Vec::new();
         for token in tokens.iter() {
-            let re = Regex::new(r"\s+").unwrap();
-            new_tokens.extend(split_token(token, &re, false));
+            new_tokens.extend(split_token(token, re, &SplitBehavior::Removed));
         }
 
         new_tokens
@@ -26,10 +30,10 @@ mod tests {
     fn whitespace() {
         let whitespace: WhiteSpace = WhiteSpace{};
 
-        let input: Vec<Token> = vec![Token{word: "Hello World!".to_string()}];
+        let input: Vec<Token> = vec![Token::new("Hello  World!")];
         let expected: Vec<Token> = vec![
-            Token{word: "Hello".to_string()},
-            Token{word: "World!".to_string()}
+            Token{word: "Hello".to_string(), start: 0, end: 5, char_start: 0, char_end: 5, ..Default::default()},
+            Token{word: "World!".to_string(), start: 7, end: 13, char_start: 7, char_end: 13, ..Default::default()}
         ];
 
         let result: Vec<Token> = whitespace.pre_tokenize(&input);
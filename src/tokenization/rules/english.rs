@@ -0,0 +1,37 @@
+use crate::tokenization::rules::{Rules, INFIXES, PREFIXES, SUFFIXES, TOKEN_MATCH};
+
+pub const ABBREVIATIONS: [&str; 21] = [
+    "Mr.", "Mrs.", "Ms.", "Dr.", "Prof.", "St.", "Jr.", "Sr.", "Inc.", "Ltd.", "Co.", "Corp.",
+    "vs.", "etc.", "e.g.", "i.e.", "a.m.", "p.m.", "No.", "Jan.", "Feb.",
+];
+
+pub fn rules() -> Rules {
+    let mut suffixes = vec![r"(?i)(?:['’](?:s|re|ve|ll|d|m)|n['’]t)$"];
+    suffixes.extend(SUFFIXES);
+
+    let mut exceptions: Vec<(&str, Vec<&str>)> = vec![
+        ("can't", vec!["ca", "n't"]),
+        ("can’t", vec!["ca", "n’t"]),
+        ("won't", vec!["wo", "n't"]),
+        ("won’t", vec!["wo", "n’t"]),
+        ("ain't", vec!["ai", "n't"]),
+        ("shan't", vec!["sha", "n't"]),
+        ("cannot", vec!["can", "not"]),
+        ("gonna", vec!["gon", "na"]),
+        ("gotta", vec!["got", "ta"]),
+        ("wanna", vec!["wan", "na"]),
+    ];
+    // contractions on their own are not split by the quote prefix
+    for contraction in ["'s", "'re", "'ve", "'ll", "'d", "'m", "n't"] {
+        exceptions.push((contraction, vec![contraction]));
+    }
+    exceptions.extend(ABBREVIATIONS.iter().map(|a| (*a, vec![*a])));
+
+    Rules {
+        prefixes: PREFIXES.to_vec(),
+        suffixes,
+        infixes: INFIXES.to_vec(),
+        token_match: TOKEN_MATCH.to_vec(),
+        exceptions,
+    }
+}
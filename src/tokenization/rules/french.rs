@@ -0,0 +1,38 @@
+use crate::tokenization::rules::{Rules, INFIXES, PREFIXES, SUFFIXES, TOKEN_MATCH};
+
+pub const ABBREVIATIONS: [&str; 10] = [
+    "M.", "MM.", "Mme.", "Mlle.", "Dr.", "etc.", "cf.", "p.ex.", "av.", "apr.",
+];
+
+// words with an apostrophe or a hyphen that are not elisions nor inversions
+const WORDS: [&str; 8] = [
+    "aujourd'hui", "aujourd’hui", "quelqu'un", "quelqu’un", "presqu'île", "prud'homme",
+    "rendez-vous", "c'est-à-dire",
+];
+
+pub fn rules() -> Rules {
+    // elisions: l'homme, qu'il, jusqu'à
+    let mut prefixes = vec![r"(?i)^(?:jusqu|lorsqu|puisqu|quoiqu|qu|[ldjnstmc])['’]"];
+    prefixes.extend(PREFIXES);
+
+    // inversions: a-t-il, dit-elle, vas-y
+    let mut suffixes = vec![
+        r"(?i)-t-(?:il|elle|on|ils|elles)$",
+        r"(?i)-(?:je|tu|il|elle|on|nous|vous|ils|elles|ce|moi|toi|le|la|les|lui|leur|y|en)$",
+    ];
+    suffixes.extend(SUFFIXES);
+
+    let exceptions: Vec<(&str, Vec<&str>)> = ABBREVIATIONS
+        .iter()
+        .chain(WORDS.iter())
+        .map(|w| (*w, vec![*w]))
+        .collect();
+
+    Rules {
+        prefixes,
+        suffixes,
+        infixes: INFIXES.to_vec(),
+        token_match: TOKEN_MATCH.to_vec(),
+        exceptions,
+    }
+}
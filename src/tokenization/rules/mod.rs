@@ -0,0 +1,223 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use pyo3::pyclass;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::tokenization::token::{Token, Word};
+use crate::tokenization::pre_tokenizers::pre_tokenizer::PreTokenizer;
+
+pub mod english;
+pub mod french;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[pyclass]
+pub enum Language {
+    English,
+    French,
+}
+
+// patterns of a language, the prefixes and suffixes are tried in order
+pub struct Rules {
+    pub prefixes: Vec<&'static str>,
+    pub suffixes: Vec<&'static str>,
+    pub infixes: Vec<&'static str>,
+    // words that are never split ('U.S.A.', urls, emails)
+    pub token_match: Vec<&'static str>,
+    // words and how they are split, the pieces put together give back the word
+    pub exceptions: Vec<(&'static str, Vec<&'static str>)>,
+}
+
+// shared by every language
+pub const PREFIXES: [&str; 1] = [r#"^[(\["'{«“‘„¿¡$£€]"#];
+pub const SUFFIXES: [&str; 2] = [r"(?:\.{2,}|…)$", r#"[)\]"'}»”’.,;:!?%]$"#];
+pub const INFIXES: [&str; 2] = [r"--+|\.{2,}|[—–…]", r"\p{L}([,;:!?])\p{L}"];
+pub const TOKEN_MATCH: [&str; 3] = [
+    r"^https?://\S+$",
+    r"^[\w.+-]+@[\w-]+(?:\.[\w-]+)+$",
+    r"^(?:\p{L}\.){2,}$",
+];
+
+// splits words with prefix, suffix and infix rules, as in the Penn Treebank
+// ('(don't' -> '(', 'do', "n't"). The text is split on whitespace first, so
+// it can replace the other pre-tokenizers.
+#[derive(Clone, Debug)]
+pub struct RuleBased {
+    language: Language,
+    prefixes: Vec<Regex>,
+    suffixes: Vec<Regex>,
+    infixes: Vec<Regex>,
+    token_match: Vec<Regex>,
+    // lowercased word -> length of its pieces
+    exceptions: HashMap<Word, Vec<usize>>,
+}
+
+impl RuleBased {
+    pub fn new(language: &Language) -> Self {
+        let rules = match language {
+            Language::English => english::rules(),
+            Language::French => french::rules(),
+        };
+        let compile = |patterns: Vec<&str>| -> Vec<Regex> {
+            patterns.iter().map(|p| Regex::new(p).unwrap()).collect()
+        };
+
+        RuleBased {
+            language: language.clone(),
+            prefixes: compile(rules.prefixes),
+            suffixes: compile(rules.suffixes),
+            infixes: compile(rules.infixes),
+            token_match: compile(rules.token_match),
+            exceptions: rules.exceptions
+                .into_iter()
+                .map(|(word, pieces)| (word.to_lowercase(), pieces.iter().map(|p| p.len()).collect()))
+                .collect(),
+        }
+    }
+
+    pub fn language(&self) -> &Language {
+        &self.language
+    }
+
+    fn exception(&self, text: &str) -> Option<&Vec<usize>> {
+        let lowercase = text.to_lowercase();
+        self.exceptions.get(&lowercase).filter(|_| lowercase.len() == text.len())
+    }
+
+    // ranges of the pieces of a word without whitespace
+    fn split_word(&self, word: &str) -> Vec<(usize, usize)> {
+        let (mut start, mut end) = (0, word.len());
+        let mut prefixes: Vec<(usize, usize)> = Vec::new();
+        let mut suffixes: Vec<(usize, usize)> = Vec::new();
+        let mut middle: Vec<(usize, usize)> = Vec::new();
+
+        while start < end {
+            let text = &word[start..end];
+            if let Some(lengths) = self.exception(text) {
+                for length in lengths.iter() {
+                    middle.push((start, start + length));
+                    start += length;
+                }
+                break;
+            }
+            if self.token_match.iter().any(|re| re.is_match(text)) {
+                middle.push((start, end));
+                break;
+            }
+            if let Some(m) = self.prefixes.iter().find_map(|re| re.find(text)) {
+                prefixes.push((start, start + m.end()));
+                start += m.end();
+                continue;
+            }
+            if let Some(m) = self.suffixes.iter().find_map(|re| re.find(text)) {
+                suffixes.push((start + m.start(), end));
+                end = start + m.start();
+                continue;
+            }
+            middle = self.split_infixes(text, start);
+            break;
+        }
+
+        prefixes.into_iter().chain(middle).chain(suffixes.into_iter().rev()).collect()
+    }
+
+    fn split_infixes(&self, text: &str, offset: usize) -> Vec<(usize, usize)> {
+        // an infix is the first group of its pattern if there is one, the
+        // rest of the match is only context
+        let mut infixes: Vec<(usize, usize)> = Vec::new();
+        for re in self.infixes.iter() {
+            let mut cursor = 0;
+            while let Some(captures) = re.captures(&text[cursor..]) {
+                let m = captures.get(1).or_else(|| captures.get(0)).unwrap();
+                if m.end() == 0 {
+                    break;
+                }
+                infixes.push((cursor + m.start(), cursor + m.end()));
+                cursor += m.end();
+            }
+        }
+        infixes.sort();
+
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        let mut cursor = 0;
+        for (start, end) in infixes {
+            if start < cursor || start == end {
+                continue;
+            }
+            if cursor < start {
+                ranges.push((cursor, start));
+            }
+            ranges.push((start, end));
+            cursor = end;
+        }
+        if cursor < text.len() {
+            ranges.push((cursor, text.len()));
+        }
+
+        ranges.into_iter().map(|(start, end)| (offset + start, offset + end)).collect()
+    }
+}
+
+impl PreTokenizer for RuleBased {
+    fn pre_tokenize(&self, tokens: &[Token]) -> Vec<Token> {
+        static WORDS: OnceLock<Regex> = OnceLock::new();
+        let whitespace = WORDS.get_or_init(|| Regex::new(r"\S+").unwrap());
+
+        let mut new_tokens: Vec<Token> = Vec::new();
+        for token in tokens.iter() {
+            let word: &str = &token.word;
+            let pieces: Vec<(Word, usize, usize)> = whitespace
+                .find_iter(word)
+                .flat_map(|m| {
+                    self.split_word(m.as_str())
+                        .into_iter()
+                        .map(move |(start, end)| (m.start() + start, m.start() + end))
+                })
+                .map(|(start, end)| (word[start..end].to_string(), start, end))
+                .collect();
+            new_tokens.extend(token.split(pieces));
+        }
+
+        new_tokens
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(language: Language, text: &str) -> Vec<String> {
+        RuleBased::new(&language)
+            .pre_tokenize(&[Token::new(text)])
+            .into_iter()
+            .map(|t| t.word)
+            .collect()
+    }
+
+    #[test]
+    fn english() {
+        assert_eq!(
+            words(Language::English, "\"Don't go to the U.S.A. with e-mail,\" she said... It costs $3.14!"),
+            vec![
+                "\"", "Do", "n't", "go", "to", "the", "U.S.A.", "with", "e-mail", ",", "\"", "she", "said", "...",
+                "It", "costs", "$", "3.14", "!"
+            ]
+        );
+        assert_eq!(words(Language::English, "I can't, Mr. Smith's"), vec!["I", "ca", "n't", ",", "Mr.", "Smith", "'s"]);
+    }
+
+    #[test]
+    fn french() {
+        assert_eq!(
+            words(Language::French, "« L'homme a-t-il vu qu'aujourd'hui c'est fini ? »"),
+            vec!["«", "L'", "homme", "a", "-t-il", "vu", "qu'", "aujourd'hui", "c'", "est", "fini", "?", "»"]
+        );
+    }
+
+    #[test]
+    fn offsets() {
+        let tokens = RuleBased::new(&Language::English).pre_tokenize(&[Token::new("(it's)")]);
+        let offsets: Vec<(usize, usize)> = tokens.iter().map(|t| (t.start, t.end)).collect();
+        assert_eq!(offsets, vec![(0, 1), (1, 3), (3, 5), (5, 6)]);
+    }
+}
@@ -0,0 +1,193 @@
+use std::{collections::HashSet, sync::OnceLock};
+
+use pyo3::{pyclass, pymethods};
+use regex::Regex;
+
+use self::punkt::{is_initial, Parameters};
+use crate::tokenization::{
+    rules::{english, french, Language},
+    token::{Token, Word}
+};
+
+pub mod punkt;
+
+// characters around the first letter of a word
+const OPENING: &[char] = &['"', '\'', '(', '[', '{', '«', '“', '‘', '¿', '¡'];
+
+// splits text into sentences after '.', '!', '?' and ellipses, unless the
+// period ends an abbreviation, an initial or a known collocation
+#[pyclass]
+pub struct SentenceSplitter {
+    // abbreviations of the language, lowercased and without their final period
+    known: HashSet<Word>,
+    parameters: Parameters,
+}
+
+impl SentenceSplitter {
+    fn new_(language: Option<&Language>) -> Self {
+        let known: &[&str] = match language {
+            Some(Language::English) => &english::ABBREVIATIONS,
+            Some(Language::French) => &french::ABBREVIATIONS,
+            None => &[],
+        };
+
+        SentenceSplitter {
+            known: known.iter().map(|a| a.trim_end_matches('.').to_lowercase()).collect(),
+            parameters: Parameters::default(),
+        }
+    }
+
+    // learn abbreviations, collocations and sentence starters, on top of the
+    // ones already known
+    fn fit_(&mut self, corpus: &str) {
+        let parameters = punkt::train(corpus, &self.known);
+        self.parameters.abbreviations.extend(parameters.abbreviations);
+        self.parameters.collocations.extend(parameters.collocations);
+        self.parameters.sentence_starters.extend(parameters.sentence_starters);
+    }
+
+    fn is_abbreviation(&self, typ: &str) -> bool {
+        self.known.contains(typ) || self.parameters.abbreviations.contains(typ) || typ.contains('.')
+    }
+
+    fn is_break(&self, punctuation: &str, previous: &str, next: &str) -> bool {
+        let next = next.trim_start_matches(OPENING);
+        let next_lowercase = next.to_lowercase();
+        let capitalized = next.chars().next().is_some_and(|c| c.is_uppercase());
+        if next.is_empty() {
+            return true;
+        }
+        if punctuation != "." {
+            return !next.chars().next().is_some_and(|c| c.is_lowercase());
+        }
+
+        let previous = previous.trim_start_matches(OPENING).to_lowercase();
+        if self.parameters.collocations.contains(&(previous.to_owned(), next_lowercase.to_owned())) {
+            return false;
+        }
+        if self.is_abbreviation(&previous) || is_initial(&previous) {
+            // without learned sentence starters, known and learned abbreviations
+            // (mostly titles) and initials are taken to come before a name, but
+            // acronyms ('U.S.') end a sentence followed by a capitalized word
+            if self.parameters.sentence_starters.is_empty() {
+                let acronym = !self.known.contains(&previous)
+                    && !self.parameters.abbreviations.contains(&previous)
+                    && !is_initial(&previous);
+                return capitalized && acronym;
+            }
+            return capitalized && self.parameters.sentence_starters.contains(&next_lowercase);
+        }
+        !next.chars().next().is_some_and(|c| c.is_lowercase())
+    }
+
+    // sentences of the text, with their position in it
+    fn split_(&self, text: &str) -> Vec<Token> {
+        static BOUNDARY: OnceLock<Regex> = OnceLock::new();
+        let boundary = BOUNDARY.get_or_init(|| Regex::new(r#"(\.+|[!?…]+)["'’”»)\]]*(\s+|$)"#).unwrap());
+
+        let mut pieces: Vec<(Word, usize, usize)> = Vec::new();
+        let mut start = text.len() - text.trim_start().len();
+
+        for captures in boundary.captures_iter(text) {
+            let (m, punctuation, space) = (captures.get(0).unwrap(), captures.get(1).unwrap(), captures.get(2).unwrap());
+            let previous = text[start..punctuation.start()].split_whitespace().last().unwrap_or("");
+            let next = text[m.end()..].split_whitespace().next().unwrap_or("");
+            if previous.is_empty() || !self.is_break(punctuation.as_str(), previous, next) {
+                continue;
+            }
+
+            pieces.push((text[start..space.start()].to_string(), start, space.start()));
+            start = m.end();
+        }
+        let rest = text[start..].trim_end();
+        if !rest.is_empty() {
+            pieces.push((rest.to_string(), start, start + rest.len()));
+        }
+
+        // keep what lies between sentences, like the tokenizers do
+        let mut sentences = Token::new(text).split(pieces);
+        let next_starts: Vec<usize> = sentences.iter().skip(1).map(|s| s.start).chain([text.len()]).collect();
+        for (sentence, next_start) in sentences.iter_mut().zip(next_starts) {
+            sentence.whitespace = text[sentence.end..next_start].to_string();
+        }
+
+        sentences
+    }
+}
+
+#[pymethods]
+impl SentenceSplitter {
+    #[new]
+    #[args(language = "None")]
+    fn new(language: Option<Language>) -> Self {
+        SentenceSplitter::new_(language.as_ref())
+    }
+
+    fn fit(&mut self, corpus: &str) {
+        SentenceSplitter::fit_(self, corpus)
+    }
+
+    fn split(&self, text: &str) -> Vec<Token> {
+        SentenceSplitter::split_(self, text)
+    }
+
+    fn sentences(&self, text: &str) -> Vec<Word> {
+        SentenceSplitter::split_(self, text).into_iter().map(|s| s.word).collect()
+    }
+
+    #[getter]
+    fn abbreviations(&self) -> Vec<Word> {
+        let mut abbreviations: Vec<Word> = self.known.union(&self.parameters.abbreviations).cloned().collect();
+        abbreviations.sort();
+        abbreviations
+    }
+
+    #[getter]
+    fn collocations(&self) -> Vec<(Word, Word)> {
+        let mut collocations: Vec<(Word, Word)> = self.parameters.collocations.iter().cloned().collect();
+        collocations.sort();
+        collocations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sentences(splitter: &SentenceSplitter, text: &str) -> Vec<Word> {
+        splitter.split_(text).into_iter().map(|s| s.word).collect()
+    }
+
+    #[test]
+    fn rules() {
+        let splitter = SentenceSplitter::new_(Some(&Language::English));
+        let text = "  Mr. Smith went to the U.S.A. in 1999. He paid $3.14 for it! \"Really?\" she asked... yes.";
+        assert_eq!(
+            sentences(&splitter, text),
+            vec!["Mr. Smith went to the U.S.A. in 1999.", "He paid $3.14 for it!", "\"Really?\" she asked... yes."]
+        );
+
+        let spans = splitter.split_(text);
+        assert_eq!(&text[spans[1].start..spans[1].end], "He paid $3.14 for it!");
+        assert_eq!(spans[0].whitespace, " ");
+    }
+
+    #[test]
+    fn untrained_acronyms() {
+        for splitter in [SentenceSplitter::new_(None), SentenceSplitter::new_(Some(&Language::English))] {
+            assert_eq!(sentences(&splitter, "He lived in the U.S. He left."), vec!["He lived in the U.S.", "He left."]);
+            assert_eq!(sentences(&splitter, "Ask J. Smith."), vec!["Ask J. Smith."]);
+        }
+    }
+
+    #[test]
+    fn learned_abbreviations() {
+        let mut splitter = SentenceSplitter::new_(None);
+        assert_eq!(sentences(&splitter, "Ask Prof. Smith."), vec!["Ask Prof.", "Smith."]);
+
+        let corpus = "The meeting is with Prof. Smith today. It went well. We met Prof. Jones and Prof. Brown. \
+            They talked. Prof. Smith left early. Then we ate. The food was good. Everyone was happy.";
+        splitter.fit_(&corpus.repeat(5));
+        assert_eq!(sentences(&splitter, "Ask Prof. Smith. He knows."), vec!["Ask Prof. Smith.", "He knows."]);
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::tokenization::token::Word;
+
+// thresholds of Kiss & Strunk (2006), "Unsupervised Multilingual Sentence
+// Boundary Detection", with the values used by NLTK
+const ABBREVIATION: f64 = 0.3;
+const COLLOCATION: f64 = 7.88;
+const SENTENCE_STARTER: f64 = 30.0;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Parameters {
+    // lowercased, without their final period ('dr', 'e.g')
+    pub abbreviations: HashSet<Word>,
+    // words that keep going after a period ('5.' 'juni'), lowercased
+    pub collocations: HashSet<(Word, Word)>,
+    // words that mostly start sentences, lowercased
+    pub sentence_starters: HashSet<Word>,
+}
+
+// a whitespace separated word of the training corpus
+struct Chunk {
+    // lowercased, without the surrounding punctuation nor the final period
+    typ: Word,
+    period_final: bool,
+    // ends with '!', '?' or an ellipsis
+    break_final: bool,
+}
+
+fn chunks(corpus: &str) -> Vec<Chunk> {
+    corpus
+        .split_whitespace()
+        .filter_map(|chunk| {
+            let chunk = chunk
+                .trim_start_matches(|c: char| !c.is_alphanumeric())
+                .trim_end_matches(|c: char| "\"')]}»”’,;:".contains(c));
+            let ellipsis = chunk.ends_with("..") || chunk.ends_with('…');
+            let break_final = ellipsis || chunk.ends_with(['!', '?']);
+            let period_final = !ellipsis && chunk.ends_with('.');
+            let typ = chunk.trim_end_matches(['.', '!', '?', '…']).to_lowercase();
+
+            (!typ.is_empty()).then_some(Chunk { typ, period_final, break_final })
+        })
+        .collect()
+}
+
+pub fn is_number(typ: &str) -> bool {
+    typ.chars().next().is_some_and(|c| c.is_ascii_digit()) && typ.chars().all(|c| c.is_ascii_digit() || ".,-".contains(c))
+}
+
+pub fn is_initial(typ: &str) -> bool {
+    let mut chars = typ.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic()) && chars.next().is_none()
+}
+
+fn xlogy(x: f64, y: f64) -> f64 {
+    if x == 0.0 { 0.0 } else { x * y.ln() }
+}
+
+// how likely 'a' is followed by a period, against a model where the period
+// would almost always follow it
+fn dunning_log_likelihood(count_a: f64, count_b: f64, count_ab: f64, n: f64) -> f64 {
+    let p1 = count_b / n;
+    let p2: f64 = 0.99;
+    let null = xlogy(count_ab, p1) + xlogy(count_a - count_ab, 1.0 - p1);
+    let alternative = xlogy(count_ab, p2) + xlogy(count_a - count_ab, 1.0 - p2);
+    -2.0 * (null - alternative)
+}
+
+// how likely 'a' and 'b' are to appear together rather than independently
+fn collocation_log_likelihood(count_a: f64, count_b: f64, count_ab: f64, n: f64) -> f64 {
+    let p = count_b / n;
+    let p1 = count_ab / count_a;
+    let p2 = if n > count_a { (count_b - count_ab) / (n - count_a) } else { 0.0 };
+    let rest = n - count_a - count_b + count_ab;
+
+    let summand1 = xlogy(count_ab, p) + xlogy(count_a - count_ab, 1.0 - p);
+    let summand2 = xlogy(count_b - count_ab, p) + xlogy(rest, 1.0 - p);
+    let summand3 = xlogy(count_ab, p1) + xlogy(count_a - count_ab, 1.0 - p1);
+    let summand4 = xlogy(count_b - count_ab, p2) + xlogy(rest, 1.0 - p2);
+    -2.0 * (summand1 + summand2 - summand3 - summand4)
+}
+
+// learn abbreviations, collocations and sentence starters from raw text,
+// 'known' abbreviations are taken into account to find sentence breaks
+pub fn train(corpus: &str, known: &HashSet<Word>) -> Parameters {
+    let chunks = chunks(corpus);
+    let n = chunks.len() as f64;
+    let mut parameters = Parameters::default();
+    if chunks.is_empty() {
+        return parameters;
+    }
+
+    // occurrences of every type, with and without a final period
+    let mut counts: HashMap<&str, (f64, f64)> = HashMap::new();
+    for chunk in chunks.iter() {
+        let count = counts.entry(&chunk.typ).or_insert((0.0, 0.0));
+        if chunk.period_final { count.0 += 1.0 } else { count.1 += 1.0 }
+    }
+    let periods = chunks.iter().filter(|c| c.period_final).count() as f64;
+
+    for (typ, (with_period, without_period)) in counts.iter() {
+        if *with_period == 0.0 || !typ.chars().any(|c| c.is_alphabetic()) {
+            continue;
+        }
+        let non_periods = typ.chars().filter(|c| *c != '.').count() as f64;
+        let internal_periods = typ.chars().filter(|c| *c == '.').count() as f64;
+
+        let likelihood = dunning_log_likelihood(with_period + without_period, periods, *with_period, n);
+        let score = likelihood * (-non_periods).exp() * (internal_periods + 1.0) * non_periods.powf(-without_period);
+        if score >= ABBREVIATION {
+            parameters.abbreviations.insert(typ.to_string());
+        }
+    }
+
+    let is_abbreviation = |typ: &str| parameters.abbreviations.contains(typ) || known.contains(typ);
+    let breaks: Vec<bool> = chunks
+        .iter()
+        .map(|c| c.break_final || (c.period_final && !is_abbreviation(&c.typ) && !is_initial(&c.typ)))
+        .collect();
+    let n_breaks = breaks.iter().filter(|b| **b).count() as f64;
+
+    // words that follow sentence breaks more often than chance
+    let mut after_break: HashMap<&str, f64> = HashMap::new();
+    for (chunk, _) in chunks.iter().skip(1).zip(breaks.iter()).filter(|(_, b)| **b) {
+        *after_break.entry(&chunk.typ).or_insert(0.0) += 1.0;
+    }
+    for (typ, count) in after_break {
+        let (with_period, without_period) = counts[typ];
+        let total = with_period + without_period;
+        let likelihood = collocation_log_likelihood(n_breaks, total, count, n);
+        if likelihood >= SENTENCE_STARTER && n / n_breaks > total / count {
+            parameters.sentence_starters.insert(typ.to_string());
+        }
+    }
+
+    // numbers and initials that are followed by the same word more often than chance
+    let mut pairs: HashMap<(&str, &str), f64> = HashMap::new();
+    for (a, b) in chunks.iter().zip(chunks.iter().skip(1)) {
+        if a.period_final && (is_number(&a.typ) || is_initial(&a.typ)) && !is_abbreviation(&a.typ) {
+            *pairs.entry((&a.typ, &b.typ)).or_insert(0.0) += 1.0;
+        }
+    }
+    for ((a, b), count) in pairs {
+        let (count_a, count_b) = (counts[a].0 + counts[a].1, counts[b].0 + counts[b].1);
+        let likelihood = collocation_log_likelihood(count_a, count_b, count, n);
+        if likelihood >= COLLOCATION && n / count_a > count_b / count && !parameters.sentence_starters.contains(b) {
+            parameters.collocations.insert((a.to_string(), b.to_string()));
+        }
+    }
+
+    parameters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn learned_abbreviations() {
+        let corpus = "The meeting is with Prof. Smith today. It went well. We met Prof. Jones and Prof. Brown. \
+            They talked. Prof. Smith left early. Then we ate. The food was good. Everyone was happy.";
+        let parameters = train(&corpus.repeat(5), &HashSet::new());
+
+        assert!(parameters.abbreviations.contains("prof"));
+        assert!(!parameters.abbreviations.contains("well"));
+        assert!(!parameters.abbreviations.contains("today"));
+    }
+}
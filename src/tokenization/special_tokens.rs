@@ -1,5 +1,89 @@
+use crate::tokenization::token::{Token, Word};
+
 pub const EOW: &str = "</w>";
 pub const SOW: &str = "<w>";
 pub const UNK: &str = "<unk>";
 pub const SOS: &str = "<s>";
 pub const EOS: &str = "</s>";
+pub const CONTINUATION: &str = "##";
+
+// tokens registered by the user ('<pad>', '<mask>', ...): they are cut out of
+// the text before the pre-tokenizers run and are never split by the models
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpecialTokens {
+    tokens: Vec<Word>,
+}
+
+impl SpecialTokens {
+    pub fn add(&mut self, token: &str) {
+        if !self.contains(token) {
+            self.tokens.push(token.to_string());
+        }
+    }
+
+    pub fn contains(&self, token: &str) -> bool {
+        self.tokens.iter().any(|t| t == token)
+    }
+
+    pub fn tokens(&self) -> &[Word] {
+        &self.tokens
+    }
+
+    // split the corpus around the special tokens, the longest one wins when
+    // several of them start at the same position
+    pub fn split(&self, corpus: &str) -> Vec<Token> {
+        let mut pieces: Vec<(Word, usize, usize)> = Vec::new();
+        let mut special: Vec<bool> = Vec::new();
+        let mut start = 0;
+        let mut cursor = 0;
+
+        while cursor < corpus.len() {
+            let found = self.tokens
+                .iter()
+                .filter(|t| corpus[cursor..].starts_with(t.as_str()))
+                .max_by_key(|t| t.len());
+
+            match found {
+                Some(token) => {
+                    if start < cursor {
+                        pieces.push((corpus[start..cursor].to_string(), start, cursor));
+                        special.push(false);
+                    }
+                    pieces.push((token.to_owned(), cursor, cursor + token.len()));
+                    special.push(true);
+                    cursor += token.len();
+                    start = cursor;
+                },
+                None => cursor += corpus[cursor..].chars().next().map_or(1, |c| c.len_utf8())
+            }
+        }
+        if start < corpus.len() || pieces.is_empty() {
+            pieces.push((corpus[start..].to_string(), start, corpus.len()));
+            special.push(false);
+        }
+
+        let mut tokens = Token::new(corpus).split(pieces);
+        for (token, special) in tokens.iter_mut().zip(special) {
+            token.special = special;
+        }
+        tokens
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_special_token_wins() {
+        let mut specials = SpecialTokens::default();
+        specials.add("<s>");
+        specials.add("<sep>");
+        specials.add("<s");
+
+        let tokens = specials.split("a<sep>b <s>");
+        let words: Vec<(&str, bool)> = tokens.iter().map(|t| (t.word.as_str(), t.special)).collect();
+        assert_eq!(words, vec![("a", false), ("<sep>", true), ("b ", false), ("<s>", true)]);
+        assert_eq!((tokens[1].start, tokens[1].end), (1, 6));
+    }
+}
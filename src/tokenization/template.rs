@@ -0,0 +1,242 @@
+use pyo3::{pyclass, pymethods, PyResult};
+use serde::{Deserialize, Serialize};
+
+use crate::tokenization::{
+    encoding::Encoding,
+    special_tokens,
+    token::Word,
+    vocabulary::Vocabulary
+};
+use crate::utils::errors::{Error, Result};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Piece {
+    // index of the sequence ($A or $B) and its type id
+    Sequence(usize, u32),
+    Special(Word, u32),
+}
+
+// wraps encoded sequences with special tokens, e.g. '<s> $A </s>' or
+// '[CLS] $A [SEP] $B:1 [SEP]:1' where ':1' gives the type id of a piece.
+// Its tokens are registered as special tokens of the tokenizer using it.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(into = "Spec", try_from = "Spec")]
+#[pyclass]
+pub struct Template {
+    #[pyo3(get)]
+    single: Word,
+    #[pyo3(get)]
+    pair: Option<Word>,
+    single_pieces: Vec<Piece>,
+    pair_pieces: Option<Vec<Piece>>,
+}
+
+// only the templates are saved, their pieces are parsed again when loading
+#[derive(Deserialize, Serialize)]
+struct Spec {
+    single: Word,
+    pair: Option<Word>,
+}
+
+impl From<Template> for Spec {
+    fn from(template: Template) -> Self {
+        Spec { single: template.single, pair: template.pair }
+    }
+}
+
+impl TryFrom<Spec> for Template {
+    type Error = Error;
+
+    fn try_from(spec: Spec) -> Result<Self> {
+        Template::new_(&spec.single, spec.pair.as_deref())
+    }
+}
+
+fn parse(template: &str, sequences: usize) -> Result<Vec<Piece>> {
+    let invalid = |message: &str| Error::Invalid(format!("invalid template '{}': {}", template, message));
+
+    let mut pieces: Vec<Piece> = Vec::new();
+    for part in template.split_whitespace() {
+        let (name, type_id) = match part.rsplit_once(':') {
+            Some((name, type_id)) if !name.is_empty() && type_id.chars().all(|c| c.is_ascii_digit()) => {
+                (name, type_id.parse::<u32>().map_err(|_| invalid("type ids must be numbers"))?)
+            },
+            _ => (part, 0),
+        };
+        pieces.push(match name {
+            "$A" => Piece::Sequence(0, type_id),
+            "$B" if sequences == 2 => Piece::Sequence(1, type_id),
+            _ if name.starts_with('$') => return Err(invalid(&format!("unknown sequence '{}'", name))),
+            _ => Piece::Special(name.to_string(), type_id),
+        });
+    }
+
+    for sequence in 0..sequences {
+        let count = pieces.iter().filter(|p| matches!(p, Piece::Sequence(s, _) if *s == sequence)).count();
+        if count != 1 {
+            return Err(invalid(&format!("{} must appear exactly once", ["$A", "$B"][sequence])));
+        }
+    }
+    Ok(pieces)
+}
+
+impl Template {
+    pub fn new_(single: &str, pair: Option<&str>) -> Result<Self> {
+        Ok(Template {
+            single: single.to_string(),
+            pair: pair.map(|p| p.to_string()),
+            single_pieces: parse(single, 1)?,
+            pair_pieces: pair.map(|p| parse(p, 2)).transpose()?,
+        })
+    }
+
+    // '<s> $A </s>', the sentences of the language models
+    pub fn sentence() -> Self {
+        Template::new_(&format!("{} $A {}", special_tokens::SOS, special_tokens::EOS), None).unwrap()
+    }
+
+    fn pieces(&self, pair: bool) -> Result<&Vec<Piece>> {
+        match pair {
+            false => Ok(&self.single_pieces),
+            true => self.pair_pieces.as_ref().ok_or(Error::Invalid("the template has no pair template".to_string()))
+        }
+    }
+
+    // every special token used by the template
+    pub fn tokens_(&self) -> Vec<Word> {
+        let mut tokens: Vec<Word> = Vec::new();
+        for piece in self.single_pieces.iter().chain(self.pair_pieces.iter().flatten()) {
+            if let Piece::Special(token, _) = piece {
+                if !tokens.contains(token) {
+                    tokens.push(token.to_owned());
+                }
+            }
+        }
+        tokens
+    }
+
+    // number of tokens added around the sequences, to take into account
+    // when truncating them
+    pub fn added_tokens_(&self, pair: bool) -> Result<usize> {
+        Ok(self.pieces(pair)?.iter().filter(|p| matches!(p, Piece::Special(..))).count())
+    }
+
+    // same as 'apply_', on words
+    pub fn apply_words_(&self, first: &[Word], second: Option<&[Word]>) -> Result<Vec<Word>> {
+        let sequences: Vec<&[Word]> = std::iter::once(first).chain(second).collect();
+        Ok(self.pieces(second.is_some())?
+            .iter()
+            .flat_map(|piece| match piece {
+                Piece::Sequence(s, _) => sequences[*s].to_vec(),
+                Piece::Special(token, _) => vec![token.to_owned()],
+            })
+            .collect())
+    }
+
+    // wrap the encodings with the template, the overflowing windows of each
+    // sequence are wrapped along with the other sequence
+    pub fn apply_(&self, first: &Encoding, second: Option<&Encoding>, vocabulary: &Vocabulary) -> Result<Encoding> {
+        let pieces = self.pieces(second.is_some())?;
+        let sequences: Vec<&Encoding> = std::iter::once(first).chain(second).collect();
+
+        let mut encoding = Encoding::default();
+        for piece in pieces.iter() {
+            match piece {
+                Piece::Sequence(s, type_id) => {
+                    let sequence = sequences[*s];
+                    encoding.ids.extend(&sequence.ids);
+                    encoding.tokens.extend(sequence.tokens.iter().cloned());
+                    encoding.offsets.extend(&sequence.offsets);
+                    encoding.type_ids.extend(std::iter::repeat_n(*type_id, sequence.len()));
+                    encoding.attention_mask.extend(&sequence.attention_mask);
+                    encoding.special_tokens_mask.extend(&sequence.special_tokens_mask);
+                },
+                Piece::Special(token, type_id) => {
+                    // tokens are registered with the tokenizer, '<unk>' is only a fallback
+                    if let Some(id) = vocabulary.id(token).or(vocabulary.unk_id()) {
+                        encoding.ids.push(id);
+                        encoding.tokens.push(token.to_owned());
+                        encoding.offsets.push((0, 0));
+                        encoding.type_ids.push(*type_id);
+                        encoding.attention_mask.push(1);
+                        encoding.special_tokens_mask.push(1);
+                    }
+                },
+            }
+        }
+
+        let main = |e: &Encoding| Encoding { overflowing: vec![], ..e.clone() };
+        for window in first.overflowing.iter() {
+            encoding.overflowing.push(self.apply_(window, second.map(main).as_ref(), vocabulary)?);
+        }
+        for window in second.map(|s| s.overflowing.iter()).into_iter().flatten() {
+            encoding.overflowing.push(self.apply_(&main(first), Some(window), vocabulary)?);
+        }
+        Ok(encoding)
+    }
+}
+
+#[pymethods]
+impl Template {
+    #[new]
+    #[args(pair = "None")]
+    fn new(single: &str, pair: Option<&str>) -> PyResult<Self> {
+        Ok(Template::new_(single, pair)?)
+    }
+
+    #[args(pair = "false")]
+    fn added_tokens(&self, pair: bool) -> PyResult<usize> {
+        Ok(Template::added_tokens_(self, pair)?)
+    }
+
+    #[args(second = "None")]
+    fn apply_words(&self, first: Vec<Word>, second: Option<Vec<Word>>) -> PyResult<Vec<Word>> {
+        Ok(Template::apply_words_(self, &first, second.as_deref())?)
+    }
+
+    #[getter]
+    fn tokens(&self) -> Vec<Word> {
+        Template::tokens_(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::wrappers::wrap_sentence;
+
+    #[test]
+    fn parsing() {
+        let template = Template::new_("[CLS] $A [SEP]", Some("[CLS] $A [SEP] $B:1 [SEP]:1")).unwrap();
+        assert_eq!(template.tokens_(), vec!["[CLS]", "[SEP]"]);
+        assert_eq!(template.added_tokens_(true).unwrap(), 3);
+
+        assert!(matches!(Template::new_("[CLS] $B", None), Err(Error::Invalid(_))));
+        assert!(matches!(Template::new_("$A", Some("$A $A $B")), Err(Error::Invalid(_))));
+    }
+
+    #[test]
+    fn sentence_words() {
+        let words: Vec<Word> = vec!["hello".to_string(), "world".to_string()];
+        assert_eq!(Template::sentence().apply_words_(&words, None).unwrap(), wrap_sentence(&words, 1, true, true));
+    }
+
+    #[test]
+    fn pair_encoding() {
+        let mut vocabulary = Vocabulary::new();
+        let cls = vocabulary.add_special("[CLS]");
+        let sep = vocabulary.add_special("[SEP]");
+        let template = Template::new_("[CLS] $A [SEP]", Some("[CLS] $A [SEP] $B:1 [SEP]:1")).unwrap();
+
+        let first = Encoding { ids: vec![10, 11], tokens: vec!["a".into(), "b".into()], offsets: vec![(0, 1), (2, 3)],
+            type_ids: vec![0, 0], attention_mask: vec![1, 1], special_tokens_mask: vec![0, 0], overflowing: vec![] };
+        let second = Encoding { ids: vec![12], tokens: vec!["c".into()], offsets: vec![(0, 1)],
+            type_ids: vec![0], attention_mask: vec![1], special_tokens_mask: vec![0], overflowing: vec![] };
+
+        let encoding = template.apply_(&first, Some(&second), &vocabulary).unwrap();
+        assert_eq!(encoding.ids, vec![cls, 10, 11, sep, 12, sep]);
+        assert_eq!(encoding.type_ids, vec![0, 0, 0, 0, 1, 1]);
+        assert_eq!(encoding.special_tokens_mask, vec![1, 0, 0, 1, 0, 1]);
+        assert_eq!(encoding.offsets[3], (0, 0));
+    }
+}
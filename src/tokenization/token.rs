@@ -4,10 +4,161 @@ use pyo3::{pyclass, pymethods, PyResult};
 
 pub type Word = String;
 
-#[derive(Clone, Debug, PartialEq)]
+// span of the original text a part of a token comes from: bytes then characters
+pub type Alignment = (usize, usize, usize, usize);
+
+#[derive(Clone, Debug, Default, PartialEq)]
 #[pyclass]
 pub struct Token {
     pub word: Word,
+    // span of the token in the original text, in bytes and in characters
+    #[pyo3(get)]
+    pub start: usize,
+    #[pyo3(get)]
+    pub end: usize,
+    #[pyo3(get)]
+    pub char_start: usize,
+    #[pyo3(get)]
+    pub char_end: usize,
+    // text between this token and the next one in the original text
+    #[pyo3(get)]
+    pub whitespace: Word,
+    // registered special token, left untouched by the pre-tokenizers and models
+    #[pyo3(get)]
+    pub special: bool,
+    // word before a pre-tokenizer replaced it with another form (its stem),
+    // the original text itself is given by the offsets
+    pub surface: Option<Word>,
+    // span of the original text of every byte of a normalized word, empty
+    // when the word is a slice of the original text
+    pub alignments: Vec<Alignment>,
+}
+
+// counts the characters of a word before byte offsets given in increasing order
+struct CharCounter<'a> {
+    word: &'a str,
+    byte: usize,
+    chars: usize,
+}
+
+impl<'a> CharCounter<'a> {
+    fn new(word: &'a str) -> Self {
+        CharCounter { word, byte: 0, chars: 0 }
+    }
+
+    // characters that end before the offset, and the ones that start before it
+    fn count(&mut self, offset: usize) -> (usize, usize) {
+        if offset < self.byte {
+            self.byte = 0;
+            self.chars = 0;
+        }
+        for c in self.word[self.byte..].chars() {
+            if self.byte + c.len_utf8() > offset {
+                break;
+            }
+            self.byte += c.len_utf8();
+            self.chars += 1;
+        }
+        (self.chars, if self.byte < offset.min(self.word.len()) { self.chars + 1 } else { self.chars })
+    }
+}
+
+impl Token {
+    // a token spanning the whole word
+    pub fn new(word: &str) -> Self {
+        Token {
+            word: word.to_string(),
+            start: 0,
+            end: word.len(),
+            char_start: 0,
+            char_end: word.chars().count(),
+            whitespace: String::new(),
+            special: false,
+            surface: None,
+            alignments: vec![],
+        }
+    }
+
+    // same span, different word
+    pub fn with_word(&self, word: Word) -> Self {
+        let alignments = if word.len() == self.word.len() { self.alignments.to_vec() } else { vec![] };
+        Token { word, alignments, ..self.clone() }
+    }
+
+    // split the token into new words covering the given byte ranges of its
+    // word, the last one keeps the whitespace that follows the token
+    pub fn split(&self, pieces: Vec<(Word, usize, usize)>) -> Vec<Token> {
+        let mut counter = CharCounter::new(&self.word);
+        let mut tokens: Vec<Token> = pieces
+            .into_iter()
+            .map(|(word, from, to)| self.sub_with(word, from, to, &mut counter))
+            .collect();
+        if let Some(last) = tokens.last_mut() {
+            last.whitespace = self.whitespace.to_owned();
+        }
+        tokens
+    }
+
+    // a new word covering the given byte range of this token's word
+    pub fn sub(&self, word: Word, from: usize, to: usize) -> Self {
+        self.sub_with(word, from, to, &mut CharCounter::new(&self.word))
+    }
+
+    fn sub_with(&self, word: Word, from: usize, to: usize, counter: &mut CharCounter) -> Self {
+        let (start, end, char_start, char_end) = self.span(from, to, counter);
+        let alignments = match self.word.get(from..to) {
+            Some(slice) if !self.alignments.is_empty() && slice == word => self.alignments[from..to].to_vec(),
+            _ => vec![]
+        };
+        // a surface is only known for the whole word
+        let surface = if from == 0 && to == self.word.len() { self.surface.clone() } else { None };
+
+        Token { word, start, end, char_start, char_end, whitespace: String::new(), special: false, surface, alignments }
+    }
+
+    // span of the original text covered by a byte range of the word. When the
+    // word no longer matches the original text and has no alignments (e.g. after
+    // case folding changed its length) offsets are clamped to the span of the token.
+    fn span(&self, from: usize, to: usize, counter: &mut CharCounter) -> Alignment {
+        if !self.alignments.is_empty() {
+            let last = to.checked_sub(1).and_then(|i| self.alignments.get(i));
+            return match (self.alignments.get(from), last) {
+                (Some(first), Some(last)) if from < to => (first.0, last.1, first.2, last.3),
+                (Some(next), _) => (next.0, next.0, next.2, next.2),
+                _ => (self.end, self.end, self.char_end, self.char_end)
+            };
+        }
+
+        // a range that starts or ends inside a character covers all of it
+        let (chars_before, _) = counter.count(from);
+        let (_, chars_until) = counter.count(to);
+        (
+            (self.start + from).min(self.end),
+            (self.start + to).min(self.end),
+            (self.char_start + chars_before).min(self.char_end),
+            (self.char_start + chars_until).min(self.char_end),
+        )
+    }
+
+    // characters of the word along with the span of the original text they come from
+    pub fn aligned_chars(&self) -> Vec<(char, Alignment)> {
+        let mut counter = CharCounter::new(&self.word);
+        self.word
+            .char_indices()
+            .map(|(i, c)| (c, self.span(i, i + c.len_utf8(), &mut counter)))
+            .collect()
+    }
+
+    // same span, with the word made of the given aligned characters
+    pub fn normalized(&self, chars: Vec<(char, Alignment)>) -> Self {
+        let word: Word = chars.iter().map(|c| c.0).collect();
+        let alignments: Vec<Alignment> = chars
+            .iter()
+            .flat_map(|(c, alignment)| std::iter::repeat_n(*alignment, c.len_utf8()))
+            .collect();
+
+        Token { word, alignments, ..self.clone() }
+    }
 }
 
 impl fmt::Display for Token {
@@ -21,4 +172,78 @@ impl Token {
     fn __str__(&self) -> PyResult<String> {
         Ok(self.word.to_string())
     }
+
+    #[getter]
+    fn offsets(&self) -> (usize, usize) {
+        (self.start, self.end)
+    }
+
+    #[getter]
+    fn char_offsets(&self) -> (usize, usize) {
+        (self.char_start, self.char_end)
+    }
+
+    #[getter]
+    fn surface(&self) -> Word {
+        self.surface.as_ref().unwrap_or(&self.word).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slices() {
+        let token = Token::new("héllo wörld");
+
+        let slice = token.sub("wörld".to_string(), 7, 13);
+        assert_eq!(slice.word, "wörld");
+        assert_eq!((slice.start, slice.end), (7, 13));
+        assert_eq!((slice.char_start, slice.char_end), (6, 11));
+    }
+
+    #[test]
+    fn nested_slices() {
+        let token = Token::new("ab cd").sub("cd".to_string(), 3, 5).sub("d".to_string(), 1, 2);
+
+        assert_eq!(token.word, "d");
+        assert_eq!((token.start, token.end), (4, 5));
+        assert_eq!((token.char_start, token.char_end), (4, 5));
+    }
+
+    #[test]
+    fn aligned_slices() {
+        let token = Token::new("xÉy").normalized(vec![
+            ('x', (0, 1, 0, 1)), ('e', (1, 3, 1, 2)), ('\u{301}', (1, 3, 1, 2)), ('y', (3, 4, 2, 3)),
+        ]);
+
+        let pieces = token.split(vec![("x".to_string(), 0, 1), ("e\u{301}y".to_string(), 1, 5)]);
+        assert_eq!((pieces[1].start, pieces[1].end), (1, 4));
+        assert_eq!((pieces[1].char_start, pieces[1].char_end), (1, 3));
+
+        let accent = pieces[1].sub("\u{301}".to_string(), 1, 3);
+        assert_eq!((accent.start, accent.end), (1, 3));
+    }
+
+    #[test]
+    fn surface_goes_to_the_whole_word() {
+        let mut token = Token::new("runs");
+        token.surface = Some("running".to_string());
+
+        let whole = token.split(vec![("runs".to_string(), 0, 4)]);
+        assert_eq!(whole[0].surface, Some("running".to_string()));
+        let pieces = token.split(vec![("run".to_string(), 0, 3), ("s".to_string(), 3, 4)]);
+        assert_eq!(pieces[0].surface, None);
+    }
+
+    #[test]
+    fn whitespace_goes_to_the_last_piece() {
+        let mut token = Token::new("ab");
+        token.whitespace = " ".to_string();
+
+        let pieces = token.split(vec![("a".to_string(), 0, 1), ("b".to_string(), 1, 2), ("".to_string(), 2, 2)]);
+        let whitespaces: Vec<&str> = pieces.iter().map(|t| t.whitespace.as_str()).collect();
+        assert_eq!(whitespaces, vec!["", "", " "]);
+    }
 }
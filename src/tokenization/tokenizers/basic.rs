@@ -1,28 +1,56 @@
-use pyo3::{pyclass, pymethods, PyResult};
+use pyo3::{pyclass, PyResult};
+use serde::{Deserialize, Serialize};
 
 use crate::tokenization::{
-    tokenizers::tokenizer::Tokenizer,
+    template::Template,
+    tokenizers::{serialization::{Pipeline, Serializable}, tokenizer::{tokenizer_pymethods, Tokenizer}},
     token::{
         Word, Token
     },
-    pre_tokenizers::PreTokenizerKind
+    normalizers::NormalizerKind,
+    pre_tokenizers::PreTokenizerKind,
+    vocabulary::Vocabulary,
+    word_counts::WordCounts
 };
+use crate::utils::errors::Result;
 
 #[pyclass]
 pub struct BasicTokenizer {
-    pub vocabulary: Vec<Word>,
+    pub vocabulary: Vocabulary,
+    normalizers: Vec<NormalizerKind>,
     pre_tokenizers: Vec<PreTokenizerKind>,
+    post_processor: Option<Template>,
+    // words given at creation, always kept
+    custom: Vec<Word>,
+    // occurrences of every word seen while fitting, in order of appearance
+    frequencies: WordCounts,
+    // pruning applied when the vocabulary is finalized
+    min_frequency: u64,
+    top_k: Option<usize>,
+    max_vocab_size: Option<usize>,
+}
+
+// the counts are saved too, so that a loaded tokenizer can keep on learning
+#[derive(Deserialize, Serialize)]
+pub struct Model {
+    custom: Vec<Word>,
+    frequencies: Vec<(Word, u64)>,
+    min_frequency: u64,
+    top_k: Option<usize>,
+    max_vocab_size: Option<usize>,
 }
 
 impl BasicTokenizer {
-    fn new_(
-        vocabulary: Option<&Vec<Word>>,
-        pre_tokenizers: Option<&Vec<PreTokenizerKind>>
+    pub fn new_(
+        vocabulary: Option<&[Word]>,
+        pre_tokenizers: Option<&[PreTokenizerKind]>
     ) -> Self {
-        let vocabulary = match vocabulary {
+        let custom = match vocabulary {
             Some(v) => v.to_vec(),
             None => vec![]
         };
+        let mut vocabulary = Vocabulary::new();
+        vocabulary.extend(custom.to_vec());
 
         let pre_tokenizers = match pre_tokenizers {
             Some(t) => t.to_vec(),
@@ -30,57 +58,165 @@ impl BasicTokenizer {
         };
 
         BasicTokenizer {
+            normalizers: vec![],
+            post_processor: None,
             pre_tokenizers,
-            vocabulary
+            vocabulary,
+            custom,
+            frequencies: WordCounts::default(),
+            min_frequency: 1,
+            top_k: None,
+            max_vocab_size: None,
         }
     }
 
-    fn pre_process(&self, corpus: &str) -> Vec<Token> {
-        let mut tokens: Vec<Token> = vec![Token{word: corpus.to_string()}];
-        for pre_tokenizer in self.pre_tokenizers.iter() {
-            tokens = pre_tokenizer.get_processor().pre_tokenize(&tokens);
+    // count the words of a document, the vocabulary is only updated by 'finalize_'
+    pub fn partial_fit_(&mut self, corpus: &str) -> Result<()> {
+        let tokens: Vec<Token> = self.pre_process(corpus)?;
+        self.frequencies.merge(&WordCounts::from_tokens(&tokens));
+        Ok(())
+    }
+
+    // rebuild the vocabulary from the words counted so far: the rare ones are
+    // dropped, then only the most frequent ones are kept if there are too many.
+    // Words keep the order in which they were first seen.
+    pub fn finalize_(&mut self) {
+        let frequencies: Vec<&(Word, u64)> = self.frequencies.iter().collect();
+        let mut kept: Vec<usize> = (0..frequencies.len())
+            .filter(|i| frequencies[*i].1 >= self.min_frequency)
+            .collect();
+
+        self.vocabulary.reset(true);
+        self.vocabulary.extend(self.custom.to_vec());
+        let available = self.max_vocab_size.map(|size| size.saturating_sub(self.vocabulary.len()));
+        let limit = match (self.top_k, available) {
+            (Some(k), Some(available)) => Some(k.min(available)),
+            (k, available) => k.or(available),
+        };
+        if let Some(limit) = limit.filter(|limit| *limit < kept.len()) {
+            // sort is stable, so ties are broken by order of appearance
+            kept.sort_by(|a, b| frequencies[*b].1.cmp(&frequencies[*a].1));
+            kept.truncate(limit);
+            kept.sort();
         }
 
-        tokens
+        for i in kept {
+            if self.max_vocab_size.is_some_and(|size| self.vocabulary.len() >= size) {
+                break;
+            }
+            self.vocabulary.add(&frequencies[i].0);
+        }
     }
 }
 
 impl Tokenizer for BasicTokenizer {
-    fn fit_(&mut self, corpus: &str) {
-        let tokens: Vec<Token> = self.pre_process(corpus);
-        self.vocabulary.extend(self.extract_vocabulary(&tokens));
+    fn normalizers(&self) -> &[NormalizerKind] {
+        &self.normalizers
+    }
+
+    fn post_processor(&self) -> Option<&Template> {
+        self.post_processor.as_ref()
+    }
+
+    fn post_processor_mut(&mut self) -> &mut Option<Template> {
+        &mut self.post_processor
+    }
+
+    fn pre_tokenizers(&self) -> &[PreTokenizerKind] {
+        &self.pre_tokenizers
+    }
+
+    fn get_vocabulary(&self) -> &Vocabulary {
+        &self.vocabulary
     }
 
-    fn transform_(&self, corpus: &str) -> Vec<Token> {
-        let tokens: Vec<Token> = self.pre_process(corpus);
-        self.tokenize(&self.vocabulary, &tokens)
+    fn get_vocabulary_mut(&mut self) -> &mut Vocabulary {
+        &mut self.vocabulary
+    }
+
+    fn fit_counts_(&mut self, counts: &WordCounts) {
+        self.frequencies.merge(counts);
+        self.finalize_();
+    }
+
+    fn transform_(&self, corpus: &str) -> Result<Vec<Token>> {
+        let tokens: Vec<Token> = self.pre_process(corpus)?;
+        Ok(self.tokenize(&self.vocabulary, &tokens))
     }
 }
 
-#[pymethods]
-impl BasicTokenizer {
-    #[new]
-    fn new(vocabulary: Vec<Word>, pre_tokenizers: Vec<PreTokenizerKind>) -> Self {
-        BasicTokenizer::new_(Some(&vocabulary), Some(&pre_tokenizers))
+impl Serializable for BasicTokenizer {
+    const NAME: &'static str = "BasicTokenizer";
+
+    type Model = Model;
+
+    fn model(&self) -> Model {
+        Model {
+            custom: self.custom.to_vec(),
+            frequencies: self.frequencies.iter().cloned().collect(),
+            min_frequency: self.min_frequency,
+            top_k: self.top_k,
+            max_vocab_size: self.max_vocab_size,
+        }
     }
 
-    fn fit(&mut self, corpus: &str) {
-        BasicTokenizer::fit_(self, corpus)
+    fn from_pipeline(pipeline: Pipeline<Model>) -> Result<Self> {
+        let mut frequencies = WordCounts::default();
+        for (word, count) in pipeline.model.frequencies {
+            frequencies.add(&word, count);
+        }
+
+        Ok(BasicTokenizer {
+            vocabulary: pipeline.vocabulary,
+            normalizers: pipeline.normalizers,
+            pre_tokenizers: pipeline.pre_tokenizers,
+            post_processor: pipeline.post_processor,
+            custom: pipeline.model.custom,
+            frequencies,
+            min_frequency: pipeline.model.min_frequency,
+            top_k: pipeline.model.top_k,
+            max_vocab_size: pipeline.model.max_vocab_size,
+        })
     }
+}
 
-    fn transform(&self, corpus: &str) -> Vec<Token> {
-        BasicTokenizer::transform_(self, corpus)
+tokenizer_pymethods!(BasicTokenizer {
+    #[new]
+    #[args(normalizers = "Vec::new()", min_frequency = "1", top_k = "None", max_vocab_size = "None")]
+    fn new(
+        vocabulary: Vec<Word>,
+        pre_tokenizers: Vec<PreTokenizerKind>,
+        normalizers: Vec<NormalizerKind>,
+        min_frequency: u64,
+        top_k: Option<usize>,
+        max_vocab_size: Option<usize>
+    ) -> Self {
+        let mut tokenizer = BasicTokenizer::new_(Some(&vocabulary), Some(&pre_tokenizers));
+        tokenizer.normalizers = normalizers;
+        tokenizer.min_frequency = min_frequency;
+        tokenizer.top_k = top_k;
+        tokenizer.max_vocab_size = max_vocab_size;
+        tokenizer
     }
 
-    fn fit_transform(&mut self, corpus: &str) -> Vec<Token> {
-        BasicTokenizer::fit_transform_(self, corpus)
+    fn partial_fit(&mut self, corpus: &str) -> PyResult<()> {
+        Ok(BasicTokenizer::partial_fit_(self, corpus)?)
+    }
+
+    fn finalize(&mut self) {
+        BasicTokenizer::finalize_(self)
     }
 
     #[getter]
     fn vocabulary(&self) -> PyResult<Vec<Word>> {
-        Ok(self.vocabulary.to_vec())
+        Ok(self.vocabulary.words())
     }
-}
+
+    #[getter]
+    fn frequencies(&self) -> Vec<(Word, u64)> {
+        self.frequencies.iter().cloned().collect()
+    }
+});
 
 #[cfg(test)]
 mod tests {
@@ -93,29 +229,71 @@ mod tests {
             None, None
         );
 
-        tokenizer.fit("Hello World!");
-        assert_eq!(tokenizer.vocabulary, vec!["Hello World!"]);
+        tokenizer.fit_("Hello World!").unwrap();
+        assert_eq!(tokenizer.vocabulary.words(), vec!["Hello World!"]);
+    }
+
+    #[test]
+    fn deduplicated_vocabulary() {
+        let mut tokenizer = BasicTokenizer::new_(
+            None,
+            Some(&[PreTokenizerKind::WhiteSpace])
+        );
+
+        tokenizer.fit_("to be or not to be").unwrap();
+        tokenizer.fit_("not to be").unwrap();
+        assert_eq!(tokenizer.vocabulary.words(), vec!["to", "be", "or", "not"]);
+    }
+
+    #[test]
+    fn pruned_vocabulary() {
+        use crate::tokenization::vocabulary::RESERVED;
+
+        let mut tokenizer = BasicTokenizer::new_(
+            Some(&["Lucas".to_string()]),
+            Some(&[PreTokenizerKind::WhiteSpace])
+        );
+        tokenizer.min_frequency = 2;
+
+        tokenizer.partial_fit_("a b c a").unwrap();
+        tokenizer.partial_fit_("d b a c e").unwrap();
+        assert_eq!(tokenizer.vocabulary.words(), vec!["Lucas"]);
+        assert_eq!((tokenizer.frequencies.get("a"), tokenizer.frequencies.get("b")), (3, 2));
+
+        tokenizer.finalize_();
+        assert_eq!(tokenizer.vocabulary.words(), vec!["Lucas", "a", "b", "c"]);
+
+        tokenizer.top_k = Some(2);
+        tokenizer.finalize_();
+        assert_eq!(tokenizer.vocabulary.words(), vec!["Lucas", "a", "b"]);
+
+        tokenizer.max_vocab_size = Some(RESERVED.len() + 2);
+        tokenizer.finalize_();
+        assert_eq!(tokenizer.vocabulary.words(), vec!["Lucas", "a"]);
+
+        let words: Vec<String> = tokenizer.transform_("a b e").unwrap().into_iter().map(|t| t.word).collect();
+        assert_eq!(words, vec!["a", "<unk>", "<unk>"]);
     }
 
     #[test]
     fn custom_vocabulary() {
         let mut tokenizer = BasicTokenizer::new_(
-            Some(&vec!["Lucas".to_string()]), None
+            Some(&["Lucas".to_string()]), None
         );
 
-        tokenizer.fit("Hello World!");
-        assert_eq!(tokenizer.vocabulary, vec!["Lucas", "Hello World!"]);
+        tokenizer.fit_("Hello World!").unwrap();
+        assert_eq!(tokenizer.vocabulary.words(), vec!["Lucas", "Hello World!"]);
     }
 
     #[test]
     fn pre_tokenizers_are_applied() {
         let mut tokenizer = BasicTokenizer::new_(
             None,
-            Some(&vec![PreTokenizerKind::WhiteSpace])
+            Some(&[PreTokenizerKind::WhiteSpace])
         );
 
-        tokenizer.fit("Hello World!");
-        assert_eq!(tokenizer.vocabulary, vec!["Hello", "World!"]);
+        tokenizer.fit_("Hello World!").unwrap();
+        assert_eq!(tokenizer.vocabulary.words(), vec!["Hello", "World!"]);
     }
 
     #[test]
@@ -124,21 +302,172 @@ mod tests {
 
         let mut tokenizer = BasicTokenizer::new_(
             None,
-            Some(&vec![
+            Some(&[
                 PreTokenizerKind::WhiteSpace,
                 PreTokenizerKind::Punctuation,
             ])
         );
 
-        tokenizer.fit("Hello World!");
-        let result: Vec<Token> = tokenizer.transform("Hello Lucas!");
+        tokenizer.fit_("Hello World!").unwrap();
+        let result: Vec<Token> = tokenizer.transform_("Hello Lucas!").unwrap();
         assert_eq!(
             result,
             vec![
-                Token{word: "Hello".to_string()},
-                Token{word: UNK.to_string()},
-                Token{word: "!".to_string()},
+                Token{word: "Hello".to_string(), start: 0, end: 5, char_start: 0, char_end: 5, whitespace: " ".to_string(), ..Default::default()},
+                Token{word: UNK.to_string(), start: 6, end: 11, char_start: 6, char_end: 11, ..Default::default()},
+                Token{word: "!".to_string(), start: 11, end: 12, char_start: 11, char_end: 12, ..Default::default()},
             ]
         );
     }
+
+    #[test]
+    fn encoding() {
+        use crate::tokenization::vocabulary::RESERVED;
+
+        let mut tokenizer = BasicTokenizer::new_(
+            None,
+            Some(&[PreTokenizerKind::WhiteSpace])
+        );
+
+        tokenizer.fit_("Hello World!").unwrap();
+        let ids: Vec<u32> = tokenizer.encode_("World! Hello Lucas").unwrap();
+        let first = RESERVED.len() as u32;
+        assert_eq!(ids, vec![first + 1, first, 0]);
+        assert_eq!(tokenizer.decode_(&ids, false), "World! Hello <unk>");
+
+        let encoding = tokenizer.encode_plus_("World! Hello Lucas").unwrap();
+        assert_eq!(encoding.ids, ids);
+        assert_eq!(encoding.offsets, vec![(0, 6), (7, 12), (13, 18)]);
+    }
+
+    #[test]
+    fn post_processing() {
+        use crate::tokenization::template::Template;
+
+        let mut tokenizer = BasicTokenizer::new_(
+            None,
+            Some(&[PreTokenizerKind::WhiteSpace])
+        );
+        tokenizer.fit_("Hello World!").unwrap();
+        let hello = tokenizer.vocabulary.id("Hello").unwrap();
+        let world = tokenizer.vocabulary.id("World!").unwrap();
+
+        let pair = tokenizer.encode_pair_("Hello", "World!").unwrap();
+        assert_eq!((pair.ids, pair.type_ids), (vec![hello, world], vec![0, 1]));
+
+        tokenizer.set_post_processor_(Some(Template::new_("[CLS] $A [SEP]", Some("[CLS] $A [SEP] $B:1 [SEP]:1")).unwrap()));
+        tokenizer.fit_("Hello again").unwrap();
+        let (cls, sep) = (tokenizer.vocabulary.id("[CLS]").unwrap(), tokenizer.vocabulary.id("[SEP]").unwrap());
+
+        let ids = tokenizer.encode_("Hello").unwrap();
+        assert_eq!(ids, vec![cls, hello, sep]);
+        assert_eq!(tokenizer.decode_(&ids, true), "Hello");
+
+        let pair = tokenizer.encode_pair_("Hello", "World!").unwrap();
+        assert_eq!(pair.ids, vec![cls, hello, sep, world, sep]);
+        assert_eq!(pair.type_ids, vec![0, 0, 0, 1, 1]);
+    }
+
+    #[test]
+    fn saved_pipeline() {
+        use crate::tokenization::{normalizers::NormalizerKind, pre_tokenizers::SplitBehavior, rules::Language, template::Template};
+
+        let mut tokenizer = BasicTokenizer::new_(
+            Some(&["Lucas".to_string()]),
+            Some(&[
+                PreTokenizerKind::regex_(r"\d+", SplitBehavior::Isolated).unwrap(),
+                PreTokenizerKind::rules(Language::French),
+                PreTokenizerKind::social(true, Some("@user".to_string())),
+            ])
+        );
+        tokenizer.normalizers = vec![NormalizerKind::NFKC, NormalizerKind::StripAccents];
+        tokenizer.min_frequency = 2;
+        tokenizer.set_post_processor_(Some(Template::new_("[CLS] $A [SEP]", None).unwrap()));
+        tokenizer.fit_("l'été 2023 @bob a dit: c'est géniaaaal! l'été 2023").unwrap();
+
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("pipeline.json");
+        let path = path.to_str().unwrap();
+        tokenizer.save_(path).unwrap();
+        let mut loaded = BasicTokenizer::load_(path).unwrap();
+
+        let corpus = "l'été 2024 @alice: c'est géniaaaal Lucas";
+        assert_eq!(loaded.vocabulary, tokenizer.vocabulary);
+        assert_eq!(loaded.transform_(corpus).unwrap(), tokenizer.transform_(corpus).unwrap());
+        assert_eq!(loaded.encode_(corpus).unwrap(), tokenizer.encode_(corpus).unwrap());
+
+        // the counts are kept, so training goes on where it stopped
+        tokenizer.fit_("2024 2024").unwrap();
+        loaded.fit_("2024 2024").unwrap();
+        assert_eq!(loaded.vocabulary.words(), tokenizer.vocabulary.words());
+    }
+
+    #[test]
+    fn callable_pre_tokenizer() {
+        use crate::utils::errors::Error;
+
+        let hyphens = PreTokenizerKind::callable_(|words| {
+            Ok(words.iter().flat_map(|w| w.split('-')).map(|w| w.to_string()).collect())
+        });
+        let mut tokenizer = BasicTokenizer::new_(None, Some(&[PreTokenizerKind::WhiteSpace, hyphens]));
+        tokenizer.add_special_tokens_(&["<a-b>"]).unwrap();
+
+        let tokens = tokenizer.fit_transform_("well-known <a-b> x-ray").unwrap();
+        let offsets: Vec<(&str, usize, usize)> = tokens.iter().map(|t| (t.word.as_str(), t.start, t.end)).collect();
+        assert_eq!(offsets, vec![("well", 0, 4), ("known", 5, 10), ("<a-b>", 11, 16), ("x", 17, 18), ("ray", 19, 22)]);
+        assert!(tokenizer.to_json_().is_err());
+
+        let failing = PreTokenizerKind::callable_(|_| Err(Error::Invalid("no".to_string())));
+        let tokenizer = BasicTokenizer::new_(None, Some(&[failing]));
+        assert_eq!(tokenizer.encode_("a b").err().map(|e| e.to_string()), Some("no".to_string()));
+    }
+
+    #[test]
+    fn batch_encoding() {
+        let mut tokenizer = BasicTokenizer::new_(
+            None,
+            Some(&[PreTokenizerKind::WhiteSpace])
+        );
+
+        tokenizer.fit_("Hello World!").unwrap();
+        let corpora = vec!["Hello", "World! Hello", "Lucas"];
+        let expected: Vec<Vec<u32>> = corpora.iter().map(|c| tokenizer.encode_(c).unwrap()).collect();
+        assert_eq!(tokenizer.encode_batch_(&corpora).unwrap(), expected);
+        assert_eq!(tokenizer.transform_batch_(&corpora).unwrap()[1], tokenizer.transform_("World! Hello").unwrap());
+    }
+
+    #[test]
+    fn detokenization() {
+        let mut tokenizer = BasicTokenizer::new_(
+            None,
+            Some(&[
+                PreTokenizerKind::WhiteSpace,
+                PreTokenizerKind::Punctuation,
+            ])
+        );
+
+        let corpus = "Hello,  World!\nIt's (almost) done...";
+        let tokens: Vec<Token> = tokenizer.fit_transform_(corpus).unwrap();
+        assert_eq!(tokenizer.detokenize_(&tokens), corpus);
+
+        let words: Vec<&str> = tokens.iter().map(|t| t.word.as_str()).collect();
+        assert_eq!(tokenizer.detokenize_words_(&words), "Hello, World! It's (almost) done...");
+    }
+
+    #[test]
+    fn normalized_offsets() {
+        let mut tokenizer = BasicTokenizer::new_(
+            None,
+            Some(&[PreTokenizerKind::WhiteSpace])
+        );
+        tokenizer.normalizers = vec![NormalizerKind::NFKC, NormalizerKind::StripAccents];
+
+        let corpus = "Ｃafé déjà";
+        let tokens: Vec<Token> = tokenizer.fit_transform_(corpus).unwrap();
+        let words: Vec<&str> = tokens.iter().map(|t| t.word.as_str()).collect();
+        assert_eq!(words, vec!["Cafe", "deja"]);
+        assert_eq!(&corpus[tokens[0].start..tokens[0].end], "Ｃafé");
+        assert_eq!(&corpus[tokens[1].start..tokens[1].end], "déjà");
+        assert_eq!((tokens[1].char_start, tokens[1].char_end), (5, 9));
+    }
 }
@@ -1,152 +1,512 @@
 use std::collections::HashMap;
+use std::fs;
 
-use pyo3::{pyclass, pymethods};
+use pyo3::{pyclass, PyResult};
+use serde::{Deserialize, Serialize};
 
-use crate::tokenization::special_tokens;
+use crate::tokenization::{
+    template::Template,
+    special_tokens,
+    tokenizers::{
+        tokenizer::{tokenizer_pymethods, Tokenizer},
+        serialization::{Pipeline, Serializable},
+        bpe_trainer::BPETrainer,
+        bpe_formats,
+        byte_level
+    },
+    token::Token,
+    normalizers::NormalizerKind,
+    pre_tokenizers::PreTokenizerKind,
+    vocabulary::Vocabulary,
+    word_counts::WordCounts
+};
+use crate::utils::errors::{Error, Result};
 
+// named after the algorithm in Python
+#[allow(clippy::upper_case_acronyms)]
 #[pyclass]
 pub struct BPE {
-    vocabulary: HashMap<String, u32>,
-    bpe_codes: HashMap<Vec<String>, u32>,
-    k: u32
+    // every known symbol, from the initial alphabet to the last merge, mapped over its id
+    vocabulary: Vocabulary,
+    merges: Vec<(String, String)>,
+    // merged pair of ids mapped over its rank
+    bpe_codes: HashMap<(u32, u32), u32>,
+    normalizers: Vec<NormalizerKind>,
+    pre_tokenizers: Vec<PreTokenizerKind>,
+    post_processor: Option<Template>,
+    k: u32,
+    // work on the bytes of the words (GPT-2 style) instead of their characters
+    byte_level: bool,
+    // encode unknown characters as '<0xNN>' byte tokens instead of '<unk>'
+    byte_fallback: bool,
+    // token of unknown characters, they are dropped from the encodings when
+    // the vocabulary does not have it
+    unk_token: String,
+    // wrap the characters of words in '<w>' and '</w>', vocabularies from
+    // other libraries have no such markers
+    word_boundaries: bool
+}
+
+// merges loaded from tiktoken ranks can share a rank, so ranks are saved along them
+#[derive(Deserialize, Serialize)]
+pub struct Model {
+    k: u32,
+    byte_level: bool,
+    byte_fallback: bool,
+    #[serde(default = "default_word_boundaries")]
+    word_boundaries: bool,
+    #[serde(default = "default_unk_token")]
+    unk_token: String,
+    merges: Vec<(String, String)>,
+    ranks: Vec<u32>,
+}
+
+fn default_word_boundaries() -> bool {
+    true
+}
+
+fn default_unk_token() -> String {
+    special_tokens::UNK.to_string()
 }
 
-#[pymethods]
 impl BPE {
-    #[new]
-    pub fn new(k: u32) -> BPE {
+    fn new_(
+        k: u32,
+        pre_tokenizers: Option<&[PreTokenizerKind]>,
+        byte_level: bool,
+        byte_fallback: bool
+    ) -> Self {
+        let pre_tokenizers = match pre_tokenizers {
+            Some(t) => t.to_vec(),
+            None => vec![]
+        };
+
         BPE {
-            vocabulary: HashMap::new(),
+            vocabulary: if byte_level { Vocabulary::empty() } else { Vocabulary::new() },
+            merges: vec![],
             bpe_codes: HashMap::new(),
-            k
+            normalizers: vec![],
+            post_processor: None,
+            pre_tokenizers,
+            k,
+            byte_level,
+            byte_fallback,
+            unk_token: special_tokens::UNK.to_string(),
+            word_boundaries: !byte_level
+        }
+    }
+
+    // symbols a word is made of before any merge
+    fn split_word(&self, word: &str) -> Vec<String> {
+        if self.byte_level {
+            let table = byte_level::bytes_to_unicode();
+            return word.bytes().map(|b| table[b as usize].to_string()).collect();
         }
+
+        let chars: Vec<String> = word.chars().map(|c| c.to_string()).collect();
+        if self.word_boundaries { wrap_word(chars) } else { chars }
     }
 
-    pub fn fit(&mut self, corpus: &str) {
-        self.vocabulary = self.get_words_count(corpus);
-        
-        for index in 0..self.k {
-            let pair_stats: HashMap<Vec<String>, u32> = self.get_pair_stats();
-            if pair_stats.is_empty() {
-                break;
+    // split a single pre-token into subwords by applying the learned merges
+    pub fn tokenize_word(&self, word: &str) -> Vec<String> {
+        self.segment_word(word).into_iter().map(|piece| piece.0).collect()
+    }
+
+    // subwords of a pre-token along with the number of bytes of the word they cover
+    fn segment_word(&self, word: &str) -> Vec<(String, usize)> {
+        let is_boundary = |symbol: &str| symbol == special_tokens::SOW || symbol == special_tokens::EOW;
+        let symbols: Vec<String> = self.split_word(word);
+        let mut word: Vec<(String, Option<u32>, usize)> = Vec::new();
+        for symbol in symbols {
+            let length = if self.byte_level { 1 } else if is_boundary(&symbol) { 0 } else { symbol.len() };
+            match self.vocabulary.id(&symbol) {
+                Some(id) => word.push((symbol, Some(id), length)),
+                None if is_boundary(&symbol) => word.push((symbol, None, 0)),
+                None if self.byte_fallback => {
+                    for byte in symbol.bytes() {
+                        let byte = byte_level::byte_token(byte);
+                        let id = self.vocabulary.id(&byte);
+                        word.push((byte, id, 1));
+                    }
+                },
+                None => word.push((self.unk_token.to_owned(), None, length))
             }
-            let best_pair: &Vec<String> = pair_stats.iter().max_by_key(|x| x.1).unwrap().0;
-            self.bpe_codes.insert(best_pair.to_vec(), index);
-            self.vocabulary = self.get_new_vocabulary(best_pair);
         }
+
+        // merge the leftmost pair with the best rank until none is left
+        loop {
+            let best = word
+                .windows(2)
+                .enumerate()
+                .filter_map(|(i, pair)| match (pair[0].1, pair[1].1) {
+                    (Some(left), Some(right)) => self.bpe_codes.get(&(left, right)).map(|rank| (*rank, i)),
+                    _ => None
+                })
+                .min();
+
+            let i = match best {
+                Some((_, i)) => i,
+                None => break
+            };
+            let merged = word[i].0.to_owned() + &word[i+1].0;
+            let id = self.vocabulary.id(&merged);
+            word[i] = (merged, id, word[i].2 + word[i+1].2);
+            word.remove(i + 1);
+        }
+
+        word.into_iter().map(|symbol| (symbol.0, symbol.2)).collect()
     }
 
-    pub fn transform(&self, corpus: &str) -> Vec<Vec<String>> {
-        corpus
-            .split(" ")
-            .collect::<Vec<&str>>()
+    fn get_words_count(&self, counts: &WordCounts) -> HashMap<Vec<String>, u64> {
+        counts
             .iter()
-            .map(|word| self.tokenize(word))
+            .map(|(word, count)| (self.split_word(word), *count))
             .collect()
     }
 
-    pub fn tokenize(&self, word: &str) -> Vec<String> {
-        let mut word: Vec<String> = wrap_word(
-            word
-                .chars()
-                .collect::<Vec<char>>()
-                .iter()
-                .map(|c| c.to_string())
-                .collect()
-        );
-        
-        loop {
-            let pairs = self.get_pairs(&word);
-            let bpe_pairs: Vec<(&Vec<String>, &u32)> = pairs
-            .iter()
-            .filter(|pair| self.bpe_codes.contains_key(pair.to_owned()))
-            .map(|pair| self.bpe_codes.get_key_value(pair).unwrap())
-            .collect();
-            
-            if bpe_pairs.is_empty() {
-                break;
-            }
-            
-            let pair_to_merge = bpe_pairs.iter().min_by_key(|pair| pair.1).unwrap().0;
-            word = self.create_new_word(word, pair_to_merge);
+    fn set_merges(&mut self, alphabet: Vec<String>, merges: Vec<(String, String)>) {
+        // every byte is part of the vocabulary so that any input can be encoded
+        let bytes: Vec<String> = if self.byte_level {
+            byte_level::bytes_to_unicode().iter().map(|c| c.to_string()).collect()
+        } else if self.byte_fallback {
+            (0..=255).map(byte_level::byte_token).collect()
+        } else {
+            vec![]
+        };
+
+        // byte-level models can encode anything and do not need reserved tokens
+        self.vocabulary.reset(!self.byte_level);
+        self.vocabulary.extend(bytes.into_iter().chain(alphabet));
+        for (left, right) in merges.iter() {
+            self.vocabulary.add(&(left.to_owned() + right));
         }
 
-        word
+        let ranks: Vec<u32> = (0..merges.len() as u32).collect();
+        self.set_codes(merges, ranks);
     }
-}
 
-impl BPE {
-    fn get_pairs(&self, word: &Vec<String>) -> Vec<Vec<String>> {
-        let mut pairs: Vec<Vec<String>> = Vec::new();
-        
-        for pair in word.windows(2) {
-            pairs.push(vec![pair[0].to_string(), pair[1].to_string()]);
+    // 'ranks' gives the priority of each merge, several merges can share the same one
+    fn set_codes(&mut self, merges: Vec<(String, String)>, ranks: Vec<u32>) {
+        self.bpe_codes = HashMap::new();
+        for ((left, right), rank) in merges.iter().zip(ranks) {
+            if let (Some(left), Some(right)) = (self.vocabulary.id(left), self.vocabulary.id(right)) {
+                self.bpe_codes.entry((left, right)).or_insert(rank);
+            }
+        }
+        self.merges = merges;
+    }
+
+    fn from_vocabulary_(
+        vocabulary: HashMap<String, u32>,
+        merges: Vec<(String, String)>,
+        pre_tokenizers: Option<&[PreTokenizerKind]>,
+        byte_level: bool,
+        byte_fallback: bool,
+        unk_token: Option<String>
+    ) -> Self {
+        let mut bpe = BPE::new_(merges.len() as u32, pre_tokenizers, byte_level, byte_fallback);
+        // only the vocabularies saved by this library know about '<w>'
+        bpe.word_boundaries = !byte_level && vocabulary.contains_key(special_tokens::SOW);
+        bpe.vocabulary = Vocabulary::from_ids(vocabulary);
+        if let Some(unk_token) = unk_token {
+            bpe.unk_token = unk_token;
         }
 
-        pairs
+        let ranks: Vec<u32> = (0..merges.len() as u32).collect();
+        bpe.set_codes(merges, ranks);
+        bpe
     }
 
-    fn create_new_word(&self, word: Vec<String>, pair_to_merge: &Vec<String>) -> Vec<String> {
-        let mut new_word: Vec<String> = Vec::new();
-        let (left, right) = (&pair_to_merge[0], &pair_to_merge[1]);
+    // tiktoken only stores ranked tokens: any split of a token into two known
+    // tokens is a merge whose rank is the one of the token
+    fn from_ranks_(ranks: &HashMap<Vec<u8>, u32>, pre_tokenizers: Option<&[PreTokenizerKind]>) -> Self {
+        let mut bpe = BPE::new_(0, pre_tokenizers, true, false);
+        let table = byte_level::bytes_to_unicode();
+        let encode = |bytes: &[u8]| -> String { bytes.iter().map(|b| table[*b as usize]).collect() };
 
-        let mut i = 0;
-        while i < word.len() {
-            if &word[i] == left && &word[i+1] == right {
-                new_word.push(left.to_owned() + right);
-                i += 1;
-            } else {
-                new_word.push(word[i].to_owned())
+        bpe.vocabulary = Vocabulary::from_ids(ranks.iter().map(|(token, rank)| (encode(token), *rank)).collect());
+
+        let mut ranked: Vec<(&Vec<u8>, &u32)> = ranks.iter().collect();
+        ranked.sort_by_key(|(_, rank)| **rank);
+
+        let mut merges: Vec<(String, String)> = Vec::new();
+        let mut merge_ranks: Vec<u32> = Vec::new();
+        for (token, rank) in ranked {
+            for split in 1..token.len() {
+                let (left, right) = token.split_at(split);
+                if ranks.contains_key(left) && ranks.contains_key(right) {
+                    merges.push((encode(left), encode(right)));
+                    merge_ranks.push(*rank);
+                }
             }
-            i += 1;
-        }
-
-        new_word
-    }
-
-    fn get_words_count(&self, text: &str) -> HashMap<String, u32> {
-        text
-            .split(" ")
-            .fold(HashMap::new(), |mut words, word| {
-                let word: Vec<String> = wrap_word(
-                    word
-                        .chars()
-                        .collect::<Vec<char>>()
-                        .iter()
-                        .map(|c| c.to_string())
-                        .collect::<Vec<String>>()
-                );
-                *words.entry(word.join(" ")).or_insert(0) += 1;
-                words
+        }
+
+        bpe.k = merges.len() as u32;
+        bpe.set_codes(merges, merge_ranks);
+        bpe
+    }
+
+    fn ranks(&self) -> Result<HashMap<Vec<u8>, u32>> {
+        if !self.byte_level {
+            return Err(Error::Invalid("only byte-level models can be saved as tiktoken ranks".to_string()));
+        }
+
+        self.vocabulary
+            .ids()
+            .iter()
+            .map(|(token, id)| {
+                let bytes: Option<Vec<u8>> = token.chars().map(byte_level::unicode_to_byte).collect();
+                match bytes {
+                    Some(bytes) => Ok((bytes, *id)),
+                    None => Err(Error::Invalid(format!("'{}' is not a byte-level token", token)))
+                }
             })
+            .collect()
     }
 
-    fn get_pair_stats(&self) -> HashMap<Vec<String>, u32> {
-        let mut stats: HashMap<Vec<String>, u32> = HashMap::new();
-        for (word, frequency) in self.vocabulary.iter() {
-            for pair in word.split(" ").map(|x| x.to_owned()).collect::<Vec<String>>().windows(2) {
-                *stats.entry(Vec::from(pair)).or_insert(0) += frequency;
-            }
+    fn from_files_(
+        vocab: &str,
+        merges: &str,
+        pre_tokenizers: Option<&[PreTokenizerKind]>,
+        byte_level: bool,
+        byte_fallback: bool
+    ) -> Result<Self> {
+        let vocabulary = bpe_formats::parse_vocab_json(&fs::read_to_string(vocab)?)?;
+        let merges = bpe_formats::parse_merges_txt(&fs::read_to_string(merges)?)?;
+
+        Ok(BPE::from_vocabulary_(vocabulary, merges, pre_tokenizers, byte_level, byte_fallback, None))
+    }
+
+    fn save_files_(&self, vocab: &str, merges: &str) -> Result<()> {
+        let content = bpe_formats::dump_merges_txt(&self.merges)?;
+        fs::write(vocab, bpe_formats::dump_vocab_json(self.vocabulary.ids()))?;
+        fs::write(merges, content)?;
+        Ok(())
+    }
+
+    fn from_tiktoken_(path: &str, pre_tokenizers: Option<&[PreTokenizerKind]>) -> Result<Self> {
+        let ranks = bpe_formats::parse_tiktoken(&fs::read_to_string(path)?)?;
+        Ok(BPE::from_ranks_(&ranks, pre_tokenizers))
+    }
+
+    fn save_tiktoken_(&self, path: &str) -> Result<()> {
+        fs::write(path, bpe_formats::dump_tiktoken(&self.ranks()?))?;
+        Ok(())
+    }
+
+    fn from_tokenizer_json_(path: &str, pre_tokenizers: Option<&[PreTokenizerKind]>) -> Result<Self> {
+        let model = bpe_formats::parse_tokenizer_json(&fs::read_to_string(path)?)?;
+        Ok(BPE::from_vocabulary_(
+            model.vocabulary,
+            model.merges,
+            pre_tokenizers,
+            model.byte_level,
+            model.byte_fallback,
+            model.unk_token
+        ))
+    }
+
+    fn save_tokenizer_json_(&self, path: &str) -> Result<()> {
+        let model = bpe_formats::TokenizerJson {
+            vocabulary: self.vocabulary.ids().clone(),
+            merges: self.merges.clone(),
+            byte_level: self.byte_level,
+            byte_fallback: self.byte_fallback,
+            unk_token: self.vocabulary.contains(&self.unk_token).then(|| self.unk_token.to_owned()),
+        };
+        fs::write(path, bpe_formats::dump_tokenizer_json(&model))?;
+        Ok(())
+    }
+}
+
+impl Tokenizer for BPE {
+    fn normalizers(&self) -> &[NormalizerKind] {
+        &self.normalizers
+    }
+
+    fn post_processor(&self) -> Option<&Template> {
+        self.post_processor.as_ref()
+    }
+
+    fn post_processor_mut(&mut self) -> &mut Option<Template> {
+        &mut self.post_processor
+    }
+
+    fn pre_tokenizers(&self) -> &[PreTokenizerKind] {
+        &self.pre_tokenizers
+    }
+
+    fn get_vocabulary(&self) -> &Vocabulary {
+        &self.vocabulary
+    }
+
+    fn get_vocabulary_mut(&mut self) -> &mut Vocabulary {
+        &mut self.vocabulary
+    }
+
+    // subwords are glued back together: byte-level tokens map back onto their
+    // bytes, the others start a new word only when they begin with '<w>'
+    fn detokenize_word(&self, word: &str) -> (Vec<u8>, bool) {
+        if self.byte_level {
+            let bytes = word
+                .chars()
+                .flat_map(|c| match byte_level::unicode_to_byte(c) {
+                    Some(byte) => vec![byte],
+                    None => c.to_string().into_bytes()
+                })
+                .collect();
+            return (bytes, true);
+        }
+        if let Some(byte) = byte_level::parse_byte_token(word) {
+            return (vec![byte], true);
+        }
+        // nothing tells where words end, so every subword is its own word
+        if !self.word_boundaries {
+            return (word.as_bytes().to_vec(), false);
         }
 
-        stats
+        let (word, continues) = match word.strip_prefix(special_tokens::SOW) {
+            Some(rest) => (rest, false),
+            None => (word, true)
+        };
+        (word.strip_suffix(special_tokens::EOW).unwrap_or(word).as_bytes().to_vec(), continues)
+    }
+
+    fn fit_counts_(&mut self, counts: &WordCounts) {
+        let words = self.get_words_count(counts);
+
+        let mut trainer = BPETrainer::new(&words);
+        let merges = trainer.train(self.k);
+        self.set_merges(trainer.alphabet(), merges);
     }
 
-    fn get_new_vocabulary(&self, best_pair: &Vec<String>) -> HashMap<String, u32> {
-        let mut new_vocabulary: HashMap<String, u32> = HashMap::new();
+    fn transform_(&self, corpus: &str) -> Result<Vec<Token>> {
+        Ok(self.pre_process(corpus)?
+            .iter()
+            .flat_map(|token| {
+                if token.special {
+                    return vec![token.clone()];
+                }
+                let mut cursor = 0;
+                token.split(
+                    self.segment_word(&token.word)
+                        .into_iter()
+                        .map(|(piece, length)| {
+                            cursor += length;
+                            (piece, cursor - length, cursor)
+                        })
+                        .collect()
+                )
+            })
+            .collect())
+    }
+}
+
+impl Serializable for BPE {
+    const NAME: &'static str = "BPE";
+
+    type Model = Model;
 
-        let pattern = regex::escape(&best_pair.join(" "));
-        let replacement = best_pair.join("");
-        for (word, frequency) in self.vocabulary.iter() {
-            let re = regex::Regex::new(&pattern).unwrap();
-            let new_word = re.replace(&word, &replacement).to_string();
-            new_vocabulary.insert(new_word, *frequency);
+    fn model(&self) -> Model {
+        let ranks: Vec<u32> = self.merges
+            .iter()
+            .enumerate()
+            .map(|(i, (left, right))| {
+                let code = self.vocabulary.id(left).zip(self.vocabulary.id(right));
+                code.and_then(|code| self.bpe_codes.get(&code)).copied().unwrap_or(i as u32)
+            })
+            .collect();
+
+        Model {
+            k: self.k,
+            byte_level: self.byte_level,
+            byte_fallback: self.byte_fallback,
+            word_boundaries: self.word_boundaries,
+            unk_token: self.unk_token.to_owned(),
+            merges: self.merges.clone(),
+            ranks,
         }
+    }
 
-        new_vocabulary
+    fn from_pipeline(pipeline: Pipeline<Model>) -> Result<Self> {
+        let model = pipeline.model;
+        if model.ranks.len() != model.merges.len() {
+            return Err(Error::Invalid("every merge must have a rank".to_string()));
+        }
+
+        let mut bpe = BPE::new_(model.k, Some(&pipeline.pre_tokenizers), model.byte_level, model.byte_fallback);
+        bpe.vocabulary = pipeline.vocabulary;
+        bpe.normalizers = pipeline.normalizers;
+        bpe.post_processor = pipeline.post_processor;
+        bpe.word_boundaries = model.word_boundaries;
+        bpe.unk_token = model.unk_token;
+        bpe.set_codes(model.merges, model.ranks);
+        Ok(bpe)
     }
 }
 
+tokenizer_pymethods!(BPE {
+    #[new]
+    #[args(byte_level = "false", byte_fallback = "false", normalizers = "Vec::new()")]
+    fn new(
+        k: u32,
+        pre_tokenizers: Vec<PreTokenizerKind>,
+        byte_level: bool,
+        byte_fallback: bool,
+        normalizers: Vec<NormalizerKind>
+    ) -> Self {
+        let mut tokenizer = BPE::new_(k, Some(&pre_tokenizers), byte_level, byte_fallback);
+        tokenizer.normalizers = normalizers;
+        tokenizer
+    }
+
+    // load GPT-2 style 'vocab.json' and 'merges.txt' files
+    #[staticmethod]
+    #[args(byte_level = "false", byte_fallback = "false", normalizers = "Vec::new()")]
+    fn from_files(
+        vocab: &str,
+        merges: &str,
+        pre_tokenizers: Vec<PreTokenizerKind>,
+        byte_level: bool,
+        byte_fallback: bool,
+        normalizers: Vec<NormalizerKind>
+    ) -> PyResult<Self> {
+        let mut tokenizer = BPE::from_files_(vocab, merges, Some(&pre_tokenizers), byte_level, byte_fallback)?;
+        tokenizer.normalizers = normalizers;
+        Ok(tokenizer)
+    }
+
+    fn save_files(&self, vocab: &str, merges: &str) -> PyResult<()> {
+        Ok(BPE::save_files_(self, vocab, merges)?)
+    }
+
+    // load a tiktoken rank file, those models are always byte-level
+    #[staticmethod]
+    #[args(normalizers = "Vec::new()")]
+    fn from_tiktoken(path: &str, pre_tokenizers: Vec<PreTokenizerKind>, normalizers: Vec<NormalizerKind>) -> PyResult<Self> {
+        let mut tokenizer = BPE::from_tiktoken_(path, Some(&pre_tokenizers))?;
+        tokenizer.normalizers = normalizers;
+        Ok(tokenizer)
+    }
+
+    fn save_tiktoken(&self, path: &str) -> PyResult<()> {
+        Ok(BPE::save_tiktoken_(self, path)?)
+    }
+
+    // load the BPE model of a Hugging Face 'tokenizer.json'
+    #[staticmethod]
+    #[args(normalizers = "Vec::new()")]
+    fn from_tokenizer_json(path: &str, pre_tokenizers: Vec<PreTokenizerKind>, normalizers: Vec<NormalizerKind>) -> PyResult<Self> {
+        let mut tokenizer = BPE::from_tokenizer_json_(path, Some(&pre_tokenizers))?;
+        tokenizer.normalizers = normalizers;
+        Ok(tokenizer)
+    }
+
+    fn save_tokenizer_json(&self, path: &str) -> PyResult<()> {
+        Ok(BPE::save_tokenizer_json_(self, path)?)
+    }
+});
+
 pub fn wrap_word(word: Vec<String>) -> Vec<String> {
     let mut wrapped: Vec<String> = Vec::new();
 
@@ -158,3 +518,290 @@ pub fn wrap_word(word: Vec<String>) -> Vec<String> {
 
     wrapped
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(tokens: &[Token]) -> Vec<&str> {
+        tokens.iter().map(|t| t.word.as_str()).collect()
+    }
+
+    #[test]
+    fn merges_are_learned() {
+        let mut tokenizer = BPE::new_(1, None, false, false);
+
+        tokenizer.fit_("aaa").unwrap();
+        let result: Vec<Token> = tokenizer.transform_("aaa").unwrap();
+        assert_eq!(words(&result), vec!["<w>", "aa", "a", "</w>"]);
+    }
+
+    #[test]
+    fn pre_tokenizers_are_applied() {
+        let mut tokenizer = BPE::new_(
+            0,
+            Some(&[PreTokenizerKind::WhiteSpace]),
+            false,
+            false
+        );
+
+        let result: Vec<Token> = tokenizer.fit_transform_("ab c").unwrap();
+        assert_eq!(
+            words(&result),
+            vec!["<w>", "a", "b", "</w>", "<w>", "c", "</w>"]
+        );
+    }
+
+    #[test]
+    fn training_is_deterministic() {
+        let corpus = "the quick brown fox jumps over the lazy dog then the fox sleeps";
+
+        let mut first = BPE::new_(20, Some(&[PreTokenizerKind::WhiteSpace]), false, false);
+        first.fit_(corpus).unwrap();
+        for _ in 0..5 {
+            let mut other = BPE::new_(20, Some(&[PreTokenizerKind::WhiteSpace]), false, false);
+            other.fit_(corpus).unwrap();
+            assert_eq!(first.merges, other.merges);
+            assert_eq!(first.vocabulary, other.vocabulary);
+        }
+    }
+
+    #[test]
+    fn streaming_training() {
+        let documents = ["the quick brown fox", "jumps over the lazy dog", "then the fox sleeps"];
+
+        let mut whole = BPE::new_(20, Some(&[PreTokenizerKind::WhiteSpace]), false, false);
+        whole.fit_(&documents.join(" ")).unwrap();
+        let mut streamed = BPE::new_(20, Some(&[PreTokenizerKind::WhiteSpace]), false, false);
+        streamed.fit_iter_(documents.iter().map(|d| Ok(d.to_string()))).unwrap();
+
+        assert_eq!(streamed.merges, whole.merges);
+        assert_eq!(streamed.vocabulary, whole.vocabulary);
+    }
+
+    #[test]
+    fn word_boundaries_are_learned() {
+        let mut tokenizer = BPE::new_(
+            10,
+            Some(&[PreTokenizerKind::WhiteSpace]),
+            false,
+            false
+        );
+
+        tokenizer.fit_("low low low lower").unwrap();
+        let result: Vec<Token> = tokenizer.transform_("low lowlow").unwrap();
+        assert_eq!(
+            words(&result),
+            vec!["<w>low</w>", "<w>low", "l", "o", "w", "</w>"]
+        );
+
+        let offsets: Vec<(usize, usize)> = result.iter().map(|t| (t.start, t.end)).collect();
+        assert_eq!(offsets, vec![(0, 3), (4, 7), (7, 8), (8, 9), (9, 10), (10, 10)]);
+    }
+
+    #[test]
+    fn detokenization() {
+        let mut tokenizer = BPE::new_(
+            10,
+            Some(&[PreTokenizerKind::WhiteSpace, PreTokenizerKind::Punctuation]),
+            false,
+            true
+        );
+
+        tokenizer.fit_("low low low lower").unwrap();
+        let corpus = "lower  low, lowé";
+        let tokens: Vec<Token> = tokenizer.transform_(corpus).unwrap();
+        assert_eq!(tokenizer.detokenize_(&tokens), corpus);
+        assert_eq!(tokenizer.decode_(&tokenizer.encode_(corpus).unwrap(), false), "lower low, lowé");
+    }
+
+    #[test]
+    fn special_tokens() {
+        let mut tokenizer = BPE::new_(
+            10,
+            Some(&[PreTokenizerKind::WhiteSpace, PreTokenizerKind::Punctuation]),
+            false,
+            false
+        );
+
+        let ids = tokenizer.add_special_tokens_(&["<sep>", "<mask>"]).unwrap();
+        tokenizer.fit_("low<sep> low low<mask> lower").unwrap();
+        assert_eq!(tokenizer.vocabulary.id("<sep>"), Some(ids[0]));
+        assert_eq!(tokenizer.vocabulary.id("<mask>"), Some(ids[1]));
+
+        let result: Vec<Token> = tokenizer.transform_("low<mask>lower<sep>").unwrap();
+        assert_eq!(words(&result), vec!["<w>low</w>", "<mask>", "<w>lower</w>", "<sep>"]);
+        assert!(result[1].special);
+
+        let encoded = tokenizer.encode_("low <mask>").unwrap();
+        assert_eq!(tokenizer.decode_(&encoded, false), "low <mask>");
+        assert_eq!(tokenizer.decode_(&encoded, true), "low");
+        assert!(tokenizer.add_special_tokens_(&[""]).is_err());
+    }
+
+    #[test]
+    fn byte_level_detokenization() {
+        let mut tokenizer = BPE::new_(
+            5,
+            Some(&[PreTokenizerKind::WhiteSpace]),
+            true,
+            false
+        );
+
+        tokenizer.fit_("éé é").unwrap();
+        let corpus = "é 😀\t!";
+        let tokens: Vec<Token> = tokenizer.transform_(corpus).unwrap();
+        assert_eq!(tokenizer.detokenize_(&tokens), corpus);
+        assert_eq!(tokenizer.decode_(&tokenizer.encode_("é😀").unwrap(), false), "é😀");
+    }
+
+    #[test]
+    fn unknown_characters() {
+        let mut tokenizer = BPE::new_(0, None, false, false);
+
+        tokenizer.fit_("ab").unwrap();
+        let result: Vec<Token> = tokenizer.transform_("aé").unwrap();
+        assert_eq!(words(&result), vec!["<w>", "a", "<unk>", "</w>"]);
+    }
+
+    #[test]
+    fn byte_fallback() {
+        let mut tokenizer = BPE::new_(0, None, false, true);
+
+        tokenizer.fit_("ab").unwrap();
+        let result: Vec<Token> = tokenizer.transform_("aé").unwrap();
+        assert_eq!(words(&result), vec!["<w>", "a", "<0xC3>", "<0xA9>", "</w>"]);
+    }
+
+    #[test]
+    fn byte_level() {
+        let mut tokenizer = BPE::new_(
+            1,
+            Some(&[PreTokenizerKind::WhiteSpace]),
+            true,
+            false
+        );
+
+        tokenizer.fit_("éé é").unwrap();
+        assert_eq!(tokenizer.vocabulary.len(), 257);
+
+        let result: Vec<Token> = tokenizer.transform_("é 😀").unwrap();
+        assert_eq!(words(&result), vec!["Ã©", "ð", "Ł", "ĺ", "Ģ"]);
+        let offsets: Vec<(usize, usize)> = result.iter().map(|t| (t.char_start, t.char_end)).collect();
+        assert_eq!(offsets, vec![(0, 1), (2, 3), (2, 3), (2, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn byte_level_ids_follow_bytes() {
+        let mut tokenizer = BPE::new_(
+            1,
+            Some(&[PreTokenizerKind::WhiteSpace]),
+            true,
+            false
+        );
+
+        tokenizer.fit_("éé é").unwrap();
+        assert_eq!(tokenizer.encode_("a é").unwrap(), vec![b'a' as u32, 256]);
+        assert_eq!(tokenizer.decode_(&[b'a' as u32], false), "a");
+    }
+
+    #[test]
+    fn pretrained_merges() {
+        let vocabulary: HashMap<String, u32> = ["l", "o", "w", "e", "r", "lo", "low", "er"]
+            .iter()
+            .enumerate()
+            .map(|(id, token)| (token.to_string(), id as u32))
+            .collect();
+        let merges = bpe_formats::parse_merges_txt("#version: 0.2\nl o\nlo w\ne r\n").unwrap();
+
+        let tokenizer = BPE::from_vocabulary_(
+            vocabulary,
+            merges,
+            Some(&[PreTokenizerKind::WhiteSpace]),
+            true,
+            false,
+            None
+        );
+        let result: Vec<Token> = tokenizer.transform_("lower owl").unwrap();
+        assert_eq!(words(&result), vec!["low", "er", "o", "w", "l"]);
+    }
+
+    // same segmentation and decoding as a Hugging Face BPE model without
+    // 'continuing_subword_prefix', 'end_of_word_suffix' nor decoder
+    #[test]
+    fn vocabulary_without_word_boundaries() {
+        let vocabulary: HashMap<String, u32> = ["l", "o", "w", "e", "r", "lo", "low", "er"]
+            .iter()
+            .enumerate()
+            .map(|(id, token)| (token.to_string(), id as u32))
+            .collect();
+        let merges = bpe_formats::parse_merges_txt("#version: 0.2\nl o\nlo w\ne r\n").unwrap();
+
+        let tokenizer = BPE::from_vocabulary_(
+            vocabulary,
+            merges,
+            Some(&[PreTokenizerKind::WhiteSpace]),
+            false,
+            false,
+            None
+        );
+        let result: Vec<Token> = tokenizer.transform_("lower owl").unwrap();
+        assert_eq!(words(&result), vec!["low", "er", "o", "w", "l"]);
+        assert_eq!(tokenizer.encode_("lower owl").unwrap(), vec![6, 7, 1, 2, 0]);
+        assert_eq!(tokenizer.detokenize_(&result), "lower owl");
+        assert_eq!(tokenizer.decode_(&[6, 7, 1, 2, 0], false), "low er o w l");
+
+        let loaded = BPE::from_json_(&tokenizer.to_json_().unwrap()).unwrap();
+        assert_eq!(loaded.transform_("lower owl").unwrap(), result);
+    }
+
+    #[test]
+    fn declared_unknown_token() {
+        let vocabulary: HashMap<String, u32> = HashMap::from([
+            ("[UNK]".to_string(), 0),
+            ("a".to_string(), 1),
+            ("b".to_string(), 2),
+            ("ab".to_string(), 3),
+        ]);
+        let merges = vec![("a".to_string(), "b".to_string())];
+
+        let tokenizer = BPE::from_vocabulary_(vocabulary.clone(), merges.clone(), None, false, false, Some("[UNK]".to_string()));
+        assert_eq!(tokenizer.vocabulary.len(), 4);
+        assert_eq!(words(&tokenizer.transform_("abc").unwrap()), vec!["ab", "[UNK]"]);
+        assert_eq!(tokenizer.encode_("abc").unwrap(), vec![3, 0]);
+
+        // without any, unknown characters are left out of the encodings
+        let tokenizer = BPE::from_vocabulary_(vocabulary, merges, None, false, false, None);
+        assert_eq!(tokenizer.vocabulary.len(), 4);
+        assert_eq!(tokenizer.encode_("abc").unwrap(), vec![3]);
+    }
+
+    #[test]
+    fn saved_models_give_identical_segmentations() {
+        let corpus = "the quick brown fox jumps over the lazy dog then the fox sleeps";
+        let pre_tokenizers = vec![PreTokenizerKind::WhiteSpace];
+
+        let mut tokenizer = BPE::new_(30, Some(&pre_tokenizers), true, false);
+        tokenizer.fit_(corpus).unwrap();
+        let expected: Vec<Token> = tokenizer.transform_("the fox jumped over a brown dog").unwrap();
+
+        let directory = tempfile::tempdir().unwrap();
+        let path = |name: &str| directory.path().join(name).to_str().unwrap().to_string();
+
+        tokenizer.save_files_(&path("vocab.json"), &path("merges.txt")).unwrap();
+        tokenizer.save_tiktoken_(&path("ranks.tiktoken")).unwrap();
+        tokenizer.save_tokenizer_json_(&path("tokenizer.json")).unwrap();
+
+        let loaded = vec![
+            BPE::from_files_(&path("vocab.json"), &path("merges.txt"), Some(&pre_tokenizers), true, false).unwrap(),
+            BPE::from_tiktoken_(&path("ranks.tiktoken"), Some(&pre_tokenizers)).unwrap(),
+            BPE::from_tokenizer_json_(&path("tokenizer.json"), Some(&pre_tokenizers)).unwrap(),
+            BPE::from_json_(&tokenizer.to_json_().unwrap()).unwrap(),
+        ];
+
+        for other in loaded {
+            assert_eq!(other.vocabulary, tokenizer.vocabulary);
+            assert_eq!(other.transform_("the fox jumped over a brown dog").unwrap(), expected);
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Map, Value};
+
+use crate::utils::errors::{Error, Result};
+
+const MERGES_HEADER: &str = "#version: 0.2";
+
+// BPE model as stored in a Hugging Face 'tokenizer.json'
+pub struct TokenizerJson {
+    pub vocabulary: HashMap<String, u32>,
+    pub merges: Vec<(String, String)>,
+    pub byte_level: bool,
+    pub byte_fallback: bool,
+    pub unk_token: Option<String>,
+}
+
+fn invalid(message: String) -> Error {
+    Error::Invalid(message)
+}
+
+// GPT-2 'vocab.json': a mapping of every token over its id
+pub fn parse_vocab_json(content: &str) -> Result<HashMap<String, u32>> {
+    serde_json::from_str(content).map_err(|e| invalid(format!("invalid vocabulary: {}", e)))
+}
+
+pub fn dump_vocab_json(vocabulary: &HashMap<String, u32>) -> String {
+    let vocabulary: Map<String, Value> = vocabulary
+        .iter()
+        .map(|(token, id)| (token.to_owned(), json!(id)))
+        .collect();
+
+    Value::Object(vocabulary).to_string()
+}
+
+// GPT-2 'merges.txt': one merge per line, by priority, after an optional version header
+pub fn parse_merges_txt(content: &str) -> Result<Vec<(String, String)>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(index, line)| !(line.is_empty() || *index == 0 && line.starts_with("#version")))
+        .map(|(index, line)| match line.split_once(' ') {
+            Some((left, right)) if !left.is_empty() && !right.is_empty() && !right.contains(' ') => {
+                Ok((left.to_string(), right.to_string()))
+            },
+            _ => Err(invalid(format!("invalid merge on line {}: '{}'", index + 1, line)))
+        })
+        .collect()
+}
+
+pub fn dump_merges_txt(merges: &[(String, String)]) -> Result<String> {
+    let mut content = String::from(MERGES_HEADER);
+    content.push('\n');
+
+    for (left, right) in merges.iter() {
+        if left.contains(char::is_whitespace) || right.contains(char::is_whitespace) {
+            return Err(invalid(format!("merge '{}' + '{}' contains whitespace", left, right)));
+        }
+        content.push_str(&format!("{} {}\n", left, right));
+    }
+
+    Ok(content)
+}
+
+// tiktoken ranks: one base64 encoded token and its rank per line
+pub fn parse_tiktoken(content: &str) -> Result<HashMap<Vec<u8>, u32>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(index, line)| {
+            let error = || invalid(format!("invalid rank on line {}: '{}'", index + 1, line));
+
+            let (token, rank) = line.split_once(' ').ok_or_else(error)?;
+            let token = STANDARD.decode(token).map_err(|_| error())?;
+            let rank = rank.trim().parse::<u32>().map_err(|_| error())?;
+            Ok((token, rank))
+        })
+        .collect()
+}
+
+pub fn dump_tiktoken(ranks: &HashMap<Vec<u8>, u32>) -> String {
+    let mut ranks: Vec<(&Vec<u8>, &u32)> = ranks.iter().collect();
+    ranks.sort_by_key(|(_, rank)| **rank);
+
+    ranks
+        .into_iter()
+        .map(|(token, rank)| format!("{} {}\n", STANDARD.encode(token), rank))
+        .collect()
+}
+
+fn is_byte_level(component: &Value) -> bool {
+    match component.get("type").and_then(Value::as_str) {
+        Some("ByteLevel") => true,
+        Some("Sequence") => ["pretokenizers", "decoders"]
+            .iter()
+            .filter_map(|key| component.get(key).and_then(Value::as_array))
+            .flatten()
+            .any(is_byte_level),
+        _ => false
+    }
+}
+
+// only the BPE model is read, along with whether the pipeline is byte-level
+pub fn parse_tokenizer_json(content: &str) -> Result<TokenizerJson> {
+    let root: Value = serde_json::from_str(content)
+        .map_err(|e| invalid(format!("invalid tokenizer.json: {}", e)))?;
+    let model = root.get("model").ok_or_else(|| invalid("missing 'model'".to_string()))?;
+
+    if model.get("type").and_then(Value::as_str) != Some("BPE") {
+        return Err(invalid("only BPE models are supported".to_string()));
+    }
+    for option in ["continuing_subword_prefix", "end_of_word_suffix"] {
+        match model.get(option) {
+            None | Some(Value::Null) => {},
+            Some(Value::String(s)) if s.is_empty() => {},
+            Some(_) => return Err(invalid(format!("'{}' is not supported", option)))
+        }
+    }
+
+    let vocabulary: HashMap<String, u32> = serde_json::from_value(
+        model.get("vocab").cloned().unwrap_or(Value::Null)
+    ).map_err(|e| invalid(format!("invalid vocabulary: {}", e)))?;
+
+    let merges = model
+        .get("merges")
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid("missing 'merges'".to_string()))?
+        .iter()
+        .map(|merge| match merge {
+            // older files store merges as "left right", newer ones as pairs
+            Value::String(s) => parse_merges_txt(s).and_then(|m| {
+                m.into_iter().next().ok_or_else(|| invalid(format!("invalid merge: '{}'", s)))
+            }),
+            Value::Array(pair) => match pair.as_slice() {
+                [Value::String(left), Value::String(right)] => Ok((left.to_owned(), right.to_owned())),
+                _ => Err(invalid(format!("invalid merge: {}", merge)))
+            },
+            _ => Err(invalid(format!("invalid merge: {}", merge)))
+        })
+        .collect::<Result<Vec<(String, String)>>>()?;
+
+    let byte_level = ["pre_tokenizer", "decoder"]
+        .iter()
+        .filter_map(|key| root.get(key))
+        .any(is_byte_level);
+    let byte_fallback = model.get("byte_fallback").and_then(Value::as_bool).unwrap_or(false);
+    let unk_token = model.get("unk_token").and_then(Value::as_str).map(|s| s.to_string());
+
+    Ok(TokenizerJson { vocabulary, merges, byte_level, byte_fallback, unk_token })
+}
+
+pub fn dump_tokenizer_json(tokenizer: &TokenizerJson) -> String {
+    let byte_level = if tokenizer.byte_level {
+        json!({
+            "type": "ByteLevel",
+            "add_prefix_space": false,
+            "trim_offsets": true,
+            "use_regex": true
+        })
+    } else {
+        Value::Null
+    };
+
+    let vocabulary: Map<String, Value> = tokenizer.vocabulary
+        .iter()
+        .map(|(token, id)| (token.to_owned(), json!(id)))
+        .collect();
+    let merges: Vec<Value> = tokenizer.merges
+        .iter()
+        .map(|(left, right)| json!([left, right]))
+        .collect();
+
+    json!({
+        "version": "1.0",
+        "truncation": null,
+        "padding": null,
+        "added_tokens": [],
+        "normalizer": null,
+        "pre_tokenizer": byte_level,
+        "post_processor": null,
+        "decoder": byte_level,
+        "model": {
+            "type": "BPE",
+            "dropout": null,
+            "unk_token": tokenizer.unk_token,
+            "continuing_subword_prefix": null,
+            "end_of_word_suffix": null,
+            "fuse_unk": false,
+            "byte_fallback": tokenizer.byte_fallback,
+            "vocab": vocabulary,
+            "merges": merges
+        }
+    }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_round_trip() {
+        let merges = vec![
+            ("Ġ".to_string(), "t".to_string()),
+            ("Ġt".to_string(), "he".to_string()),
+        ];
+
+        let content = dump_merges_txt(&merges).unwrap();
+        assert_eq!(content, "#version: 0.2\nĠ t\nĠt he\n");
+        assert_eq!(parse_merges_txt(&content).unwrap(), merges);
+    }
+
+    #[test]
+    fn invalid_merges() {
+        assert!(parse_merges_txt("#version: 0.2\nab\n").is_err());
+        assert!(dump_merges_txt(&[(" ".to_string(), "a".to_string())]).is_err());
+    }
+
+    #[test]
+    fn tiktoken_round_trip() {
+        let ranks: HashMap<Vec<u8>, u32> = HashMap::from([
+            (b"a".to_vec(), 0),
+            (b" the".to_vec(), 1),
+            (vec![0xE2, 0x80], 2),
+        ]);
+
+        let content = dump_tiktoken(&ranks);
+        assert_eq!(content, "YQ== 0\nIHRoZQ== 1\n4oA= 2\n");
+        assert_eq!(parse_tiktoken(&content).unwrap(), ranks);
+    }
+
+    #[test]
+    fn tokenizer_json() {
+        let content = r#"{
+            "pre_tokenizer": {"type": "Sequence", "pretokenizers": [{"type": "ByteLevel"}]},
+            "model": {
+                "type": "BPE",
+                "vocab": {"a": 0, "b": 1, "ab": 2},
+                "merges": ["a b"]
+            }
+        }"#;
+
+        let tokenizer = parse_tokenizer_json(content).unwrap();
+        assert!(tokenizer.byte_level);
+        assert_eq!(tokenizer.unk_token, None);
+        assert_eq!(tokenizer.merges, vec![("a".to_string(), "b".to_string())]);
+
+        let tokenizer = parse_tokenizer_json(&dump_tokenizer_json(&tokenizer)).unwrap();
+        assert!(tokenizer.byte_level);
+        assert!(!tokenizer.byte_fallback);
+        assert_eq!(tokenizer.vocabulary.len(), 3);
+        assert_eq!(tokenizer.merges, vec![("a".to_string(), "b".to_string())]);
+    }
+
+    #[test]
+    fn unknown_token() {
+        let content = r#"{"model": {"type": "BPE", "vocab": {"[UNK]": 0}, "merges": [], "unk_token": "[UNK]"}}"#;
+
+        let tokenizer = parse_tokenizer_json(content).unwrap();
+        assert_eq!(tokenizer.unk_token.as_deref(), Some("[UNK]"));
+        let tokenizer = parse_tokenizer_json(&dump_tokenizer_json(&tokenizer)).unwrap();
+        assert_eq!(tokenizer.unk_token.as_deref(), Some("[UNK]"));
+    }
+
+    #[test]
+    fn unsupported_tokenizer_json() {
+        let content = r#"{"model": {"type": "WordPiece", "vocab": {}}}"#;
+        assert!(parse_tokenizer_json(content).is_err());
+
+        let content = r#"{"model": {"type": "BPE", "vocab": {}, "merges": [], "end_of_word_suffix": "</w>"}}"#;
+        assert!(parse_tokenizer_json(content).is_err());
+    }
+}
@@ -0,0 +1,248 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
+
+type Pair = (u32, u32);
+
+// a candidate merge in the priority queue. Entries are never updated in place:
+// when the count of a pair changes a new entry is pushed and stale ones are
+// skipped when popped.
+#[derive(Eq, PartialEq)]
+struct Candidate {
+    count: u64,
+    pair: Pair,
+    text: (String, String),
+}
+
+impl Ord for Candidate {
+    // highest count first, then lexicographically smallest pair
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.count
+            .cmp(&other.count)
+            .then_with(|| other.text.cmp(&self.text))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+pub struct BPETrainer {
+    symbols: Vec<String>,
+    alphabet_size: usize,
+    symbol_ids: HashMap<String, u32>,
+    words: Vec<Vec<u32>>,
+    counts: Vec<u64>,
+    pair_counts: HashMap<Pair, u64>,
+    pair_words: HashMap<Pair, HashSet<usize>>,
+    queue: BinaryHeap<Candidate>,
+}
+
+impl BPETrainer {
+    // 'words' maps every distinct word, already split into its initial symbols,
+    // over its frequency in the corpus
+    pub fn new(words: &HashMap<Vec<String>, u64>) -> Self {
+        let mut trainer = BPETrainer {
+            symbols: vec![],
+            alphabet_size: 0,
+            symbol_ids: HashMap::new(),
+            words: vec![],
+            counts: vec![],
+            pair_counts: HashMap::new(),
+            pair_words: HashMap::new(),
+            queue: BinaryHeap::new(),
+        };
+
+        // the alphabet is sorted so that ids do not depend on hashing order
+        let alphabet: BTreeSet<&String> = words.keys().flatten().collect();
+        for symbol in alphabet {
+            trainer.add_symbol(symbol);
+        }
+        trainer.alphabet_size = trainer.symbols.len();
+
+        for (index, (word, count)) in words.iter().enumerate() {
+            let word: Vec<u32> = word.iter().map(|s| trainer.symbol_ids[s]).collect();
+            for pair in word.windows(2) {
+                let pair = (pair[0], pair[1]);
+                *trainer.pair_counts.entry(pair).or_insert(0) += count;
+                trainer.pair_words.entry(pair).or_default().insert(index);
+            }
+            trainer.words.push(word);
+            trainer.counts.push(*count);
+        }
+
+        let pairs: Vec<(Pair, u64)> = trainer.pair_counts.iter().map(|(p, c)| (*p, *c)).collect();
+        for (pair, count) in pairs {
+            trainer.push(pair, count);
+        }
+
+        trainer
+    }
+
+    // symbols known before any merge, sorted
+    pub fn alphabet(&self) -> Vec<String> {
+        self.symbols[..self.alphabet_size].to_vec()
+    }
+
+    // learn up to 'k' merges, in the order they were applied
+    pub fn train(&mut self, k: u32) -> Vec<(String, String)> {
+        let mut merges: Vec<(String, String)> = Vec::new();
+
+        while merges.len() < k as usize {
+            let candidate = match self.queue.pop() {
+                Some(c) => c,
+                None => break
+            };
+
+            let count = *self.pair_counts.get(&candidate.pair).unwrap_or(&0);
+            if count != candidate.count {
+                if count > 0 {
+                    self.push(candidate.pair, count);
+                }
+                continue;
+            }
+
+            self.merge(candidate.pair);
+            merges.push(candidate.text);
+        }
+
+        merges
+    }
+
+    fn add_symbol(&mut self, symbol: &str) -> u32 {
+        if let Some(id) = self.symbol_ids.get(symbol) {
+            return *id;
+        }
+        let id = self.symbols.len() as u32;
+        self.symbols.push(symbol.to_string());
+        self.symbol_ids.insert(symbol.to_string(), id);
+        id
+    }
+
+    fn push(&mut self, pair: Pair, count: u64) {
+        self.queue.push(Candidate {
+            count,
+            pair,
+            text: (self.symbols[pair.0 as usize].to_string(), self.symbols[pair.1 as usize].to_string()),
+        });
+    }
+
+    // replace every occurrence of 'pair' and update the counts of the pairs around it
+    fn merge(&mut self, pair: Pair) {
+        let merged = self.symbols[pair.0 as usize].to_string() + &self.symbols[pair.1 as usize];
+        let new_id = self.add_symbol(&merged);
+
+        let indices = self.pair_words.remove(&pair).unwrap_or_default();
+        self.pair_counts.remove(&pair);
+
+        let mut changed: HashSet<Pair> = HashSet::new();
+        for index in indices {
+            let word = &self.words[index];
+            let count = self.counts[index];
+
+            let mut new_word: Vec<u32> = Vec::with_capacity(word.len());
+            let mut i = 0;
+            while i < word.len() {
+                if i + 1 < word.len() && word[i] == pair.0 && word[i + 1] == pair.1 {
+                    new_word.push(new_id);
+                    i += 2;
+                } else {
+                    new_word.push(word[i]);
+                    i += 1;
+                }
+            }
+
+            for old in word.windows(2) {
+                let old = (old[0], old[1]);
+                if old == pair {
+                    continue;
+                }
+                if let Some(c) = self.pair_counts.get_mut(&old) {
+                    *c -= count;
+                }
+            }
+            for new in new_word.windows(2) {
+                let new = (new[0], new[1]);
+                *self.pair_counts.entry(new).or_insert(0) += count;
+                self.pair_words.entry(new).or_default().insert(index);
+                if new.0 == new_id || new.1 == new_id {
+                    changed.insert(new);
+                }
+            }
+
+            self.words[index] = new_word;
+        }
+
+        // pairs whose count dropped are fixed lazily when popped, only the
+        // ones built around the new symbol need a fresh entry
+        let mut changed: Vec<Pair> = changed.into_iter().collect();
+        changed.sort();
+        for pair in changed {
+            let count = self.pair_counts[&pair];
+            if count > 0 {
+                self.push(pair, count);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(word: &str) -> Vec<String> {
+        word.chars().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn most_frequent_pair_first() {
+        let words: HashMap<Vec<String>, u64> = HashMap::from([
+            (split("low"), 5),
+            (split("lower"), 2),
+            (split("newest"), 6),
+            (split("widest"), 3),
+        ]);
+
+        let merges = BPETrainer::new(&words).train(3);
+        assert_eq!(
+            merges,
+            vec![
+                ("e".to_string(), "s".to_string()),
+                ("es".to_string(), "t".to_string()),
+                ("l".to_string(), "o".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn ties_are_broken_lexicographically() {
+        let words: HashMap<Vec<String>, u64> = HashMap::from([
+            (split("cd"), 1),
+            (split("ab"), 1),
+        ]);
+
+        let merges = BPETrainer::new(&words).train(2);
+        assert_eq!(
+            merges,
+            vec![
+                ("a".to_string(), "b".to_string()),
+                ("c".to_string(), "d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn overlapping_pairs() {
+        let words: HashMap<Vec<String>, u64> = HashMap::from([(split("aaaa"), 1)]);
+
+        let mut trainer = BPETrainer::new(&words);
+        assert_eq!(
+            trainer.train(2),
+            vec![
+                ("a".to_string(), "a".to_string()),
+                ("aa".to_string(), "aa".to_string()),
+            ]
+        );
+    }
+}
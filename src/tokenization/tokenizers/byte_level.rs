@@ -0,0 +1,69 @@
+use std::sync::OnceLock;
+
+// GPT-2 maps every byte onto a printable character so that any byte sequence
+// can go through the merges as if it was regular text: printable latin-1 bytes
+// map onto themselves, the others are shifted after U+0100.
+pub fn bytes_to_unicode() -> &'static [char; 256] {
+    static TABLE: OnceLock<[char; 256]> = OnceLock::new();
+
+    TABLE.get_or_init(|| {
+        let mut table = ['\0'; 256];
+        let mut shift = 0;
+        for byte in 0..=255u8 {
+            let printable = matches!(byte, b'!'..=b'~' | 0xA1..=0xAC | 0xAE..=0xFF);
+            table[byte as usize] = if printable {
+                byte as char
+            } else {
+                shift += 1;
+                char::from_u32(255 + shift).unwrap()
+            };
+        }
+        table
+    })
+}
+
+pub fn unicode_to_byte(c: char) -> Option<u8> {
+    bytes_to_unicode().iter().position(|m| *m == c).map(|b| b as u8)
+}
+
+// textual representation of a byte used when falling back on raw bytes
+pub fn byte_token(byte: u8) -> String {
+    format!("<0x{:02X}>", byte)
+}
+
+pub fn parse_byte_token(token: &str) -> Option<u8> {
+    let hex = token.strip_prefix("<0x")?.strip_suffix('>')?;
+    if hex.len() != 2 {
+        return None;
+    }
+    u8::from_str_radix(hex, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn printable_bytes_are_kept() {
+        let table = bytes_to_unicode();
+        assert_eq!(table[b'a' as usize], 'a');
+        assert_eq!(table[b' ' as usize], 'Ġ');
+        assert_eq!(table[b'\n' as usize], 'Ċ');
+    }
+
+    #[test]
+    fn mapping_is_a_bijection() {
+        let table = bytes_to_unicode();
+        for byte in 0..=255u8 {
+            assert_eq!(unicode_to_byte(table[byte as usize]), Some(byte));
+        }
+    }
+
+    #[test]
+    fn byte_tokens() {
+        assert_eq!(byte_token(0x0A), "<0x0A>");
+        assert_eq!(byte_token(0xE2), "<0xE2>");
+        assert_eq!(parse_byte_token("<0xE2>"), Some(0xE2));
+        assert_eq!(parse_byte_token("<0xE>"), None);
+    }
+}
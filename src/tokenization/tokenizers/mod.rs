@@ -1,3 +1,9 @@
 pub mod basic;
 pub mod bpe;
+pub mod bpe_formats;
+pub mod bpe_trainer;
+pub mod serialization;
+pub mod byte_level;
 pub mod tokenizer;
+pub mod unigram;
+pub mod wordpiece;
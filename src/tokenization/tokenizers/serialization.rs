@@ -0,0 +1,105 @@
+use std::fs;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::tokenization::{
+    normalizers::NormalizerKind,
+    pre_tokenizers::PreTokenizerKind,
+    template::Template,
+    tokenizers::tokenizer::Tokenizer,
+    vocabulary::Vocabulary
+};
+use crate::utils::errors::{Error, Result};
+
+// bumped whenever a saved pipeline can no longer be read the same way
+pub const FORMAT_VERSION: u32 = 1;
+
+// everything needed to rebuild a tokenizer: the stages shared by every
+// tokenizer, and the options and learned state of its model
+#[derive(Deserialize, Serialize)]
+pub struct Pipeline<M> {
+    pub version: u32,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub normalizers: Vec<NormalizerKind>,
+    pub pre_tokenizers: Vec<PreTokenizerKind>,
+    pub post_processor: Option<Template>,
+    pub vocabulary: Vocabulary,
+    pub model: M,
+}
+
+// tokenizers that can be saved to and loaded from a JSON file. A new
+// tokenizer only has to describe its model.
+pub trait Serializable: Tokenizer + Sized {
+    // name of the tokenizer in the saved files, checked when loading
+    const NAME: &'static str;
+
+    type Model: Serialize + DeserializeOwned;
+
+    fn model(&self) -> Self::Model;
+
+    // the vocabulary, normalizers and pre-tokenizers are the saved ones
+    fn from_pipeline(pipeline: Pipeline<Self::Model>) -> Result<Self>;
+
+    fn to_json_(&self) -> Result<String> {
+        let pipeline = Pipeline {
+            version: FORMAT_VERSION,
+            kind: Self::NAME.to_string(),
+            normalizers: self.normalizers().to_vec(),
+            pre_tokenizers: self.pre_tokenizers().to_vec(),
+            post_processor: self.post_processor().cloned(),
+            vocabulary: self.get_vocabulary().clone(),
+            model: self.model(),
+        };
+        serde_json::to_string_pretty(&pipeline).map_err(|e| Error::Invalid(format!("invalid pipeline: {}", e)))
+    }
+
+    fn from_json_(json: &str) -> Result<Self> {
+        let invalid = |e: serde_json::Error| Error::Invalid(format!("invalid pipeline: {}", e));
+
+        // the header is checked first so that files from other versions or
+        // tokenizers get a clear error instead of a missing field
+        let value: serde_json::Value = serde_json::from_str(json).map_err(invalid)?;
+        match value.get("version").and_then(|v| v.as_u64()) {
+            Some(version) if version <= FORMAT_VERSION as u64 => {},
+            Some(version) => return Err(Error::Invalid(format!("unsupported pipeline version {}", version))),
+            None => return Err(Error::Invalid("invalid pipeline: missing version".to_string())),
+        }
+        match value.get("type").and_then(|t| t.as_str()) {
+            Some(kind) if kind == Self::NAME => {},
+            kind => return Err(Error::Invalid(format!(
+                "expected a '{}' pipeline, found '{}'", Self::NAME, kind.unwrap_or_default()
+            ))),
+        }
+
+        Self::from_pipeline(serde_json::from_value(value).map_err(invalid)?)
+    }
+
+    fn save_(&self, path: &str) -> Result<()> {
+        fs::write(path, self.to_json_()?)?;
+        Ok(())
+    }
+
+    fn load_(path: &str) -> Result<Self> {
+        Self::from_json_(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenization::tokenizers::{basic::BasicTokenizer, wordpiece::WordPiece};
+
+    #[test]
+    fn checked_header() {
+        let json = BasicTokenizer::new_(None, None).to_json_().unwrap();
+        assert!(BasicTokenizer::from_json_(&json).is_ok());
+
+        let error = WordPiece::from_json_(&json).err().unwrap();
+        assert_eq!(error.to_string(), "expected a 'WordPiece' pipeline, found 'BasicTokenizer'");
+
+        let newer = json.replacen(&format!("\"version\": {}", FORMAT_VERSION), "\"version\": 99", 1);
+        let error = BasicTokenizer::from_json_(&newer).err().unwrap();
+        assert_eq!(error.to_string(), "unsupported pipeline version 99");
+    }
+}
@@ -1,28 +1,386 @@
 use itertools::Itertools;
+use pyo3::{types::{PyAny, PyIterator}, Py, PyResult, Python};
+use rayon::prelude::*;
 
-use crate::tokenization::{token::{Token, Word}, special_tokens::UNK};
+use crate::tokenization::{
+    encoding::Encoding,
+    token::{Token, Word},
+    special_tokens::UNK,
+    template::Template,
+    normalizers::{self, NormalizerKind},
+    pre_tokenizers::PreTokenizerKind,
+    vocabulary::Vocabulary,
+    word_counts::WordCounts
+};
+use crate::utils::{errors::{Error, Result}, files};
+
+// documents counted together when fitting on a stream of documents
+const BATCH_SIZE: usize = 1000;
 
 pub trait Tokenizer {
-    fn transform_(&self, corpus: &str) -> Vec<Token>;
+    fn transform_(&self, corpus: &str) -> Result<Vec<Token>>;
+
+    // learn from the occurrences of the pre-tokens of a corpus
+    fn fit_counts_(&mut self, _counts: &WordCounts) {}
+
+    fn fit_(&mut self, corpus: &str) -> Result<()> {
+        let counts = WordCounts::from_tokens(&self.pre_process(corpus)?);
+        self.fit_counts_(&counts);
+        Ok(())
+    }
+
+    // pre-tokens of the documents are counted in parallel, then merged in order
+    fn count_words_(&self, corpora: &[&str]) -> Result<WordCounts> where Self: Sync {
+        let counts: Vec<WordCounts> = corpora
+            .par_iter()
+            .map(|corpus| Ok(WordCounts::from_tokens(&self.pre_process(corpus)?)))
+            .collect::<Result<_>>()?;
+
+        let mut merged = WordCounts::default();
+        for c in counts.iter() {
+            merged.merge(c);
+        }
+        Ok(merged)
+    }
 
-    fn fit_(&mut self, _corpus: &str) {}
+    // fit on a stream of documents that does not have to fit in memory
+    fn fit_iter_<I: Iterator<Item = Result<String>>>(&mut self, corpora: I) -> Result<()> where Self: Sync + Sized {
+        let mut counts = WordCounts::default();
+        let mut batch: Vec<String> = Vec::with_capacity(BATCH_SIZE);
+        for corpus in corpora {
+            batch.push(corpus?);
+            if batch.len() == BATCH_SIZE {
+                let corpora: Vec<&str> = batch.iter().map(|c| c.as_str()).collect();
+                counts.merge(&self.count_words_(&corpora)?);
+                batch.clear();
+            }
+        }
+        let corpora: Vec<&str> = batch.iter().map(|c| c.as_str()).collect();
+        counts.merge(&self.count_words_(&corpora)?);
+
+        self.fit_counts_(&counts);
+        Ok(())
+    }
 
-    fn fit_transform_(&mut self, corpus: &str) -> Vec<Token> {
-        self.fit_(corpus);
+    // every line of the files is a document, see 'files::read_lines'
+    fn fit_files_(&mut self, paths: &[&str]) -> Result<()> where Self: Sync + Sized {
+        self.fit_iter_(files::read_lines(paths)?)
+    }
+
+    fn fit_transform_(&mut self, corpus: &str) -> Result<Vec<Token>> {
+        self.fit_(corpus)?;
         self.transform_(corpus)
     }
 
-    fn extract_vocabulary(&self, tokens: &Vec<Token>) -> Vec<Word> {
-        tokens.into_iter().map(|t| t.word.to_string()).unique().collect()
+    fn normalizers(&self) -> &[NormalizerKind];
+
+    fn pre_tokenizers(&self) -> &[PreTokenizerKind];
+
+    fn post_processor(&self) -> Option<&Template>;
+
+    fn post_processor_mut(&mut self) -> &mut Option<Template>;
+
+    fn get_vocabulary(&self) -> &Vocabulary;
+
+    fn get_vocabulary_mut(&mut self) -> &mut Vocabulary;
+
+    // register tokens that are never split, and return their ids
+    fn add_special_tokens_(&mut self, tokens: &[&str]) -> Result<Vec<u32>> {
+        if tokens.iter().any(|t| t.is_empty()) {
+            return Err(Error::Invalid("special tokens cannot be empty".to_string()));
+        }
+        Ok(tokens.iter().map(|t| self.get_vocabulary_mut().add_special(t)).collect())
+    }
+
+    // the tokens of the template become special tokens, so they keep their ids
+    fn set_post_processor_(&mut self, template: Option<Template>) {
+        for token in template.iter().flat_map(|t| t.tokens_()) {
+            self.get_vocabulary_mut().add_special(&token);
+        }
+        *self.post_processor_mut() = template;
+    }
+
+    // ids of the tokens, the ones missing from the vocabulary get the id of
+    // '<unk>', wrapped with the template of the post-processor if any
+    fn encode_(&self, corpus: &str) -> Result<Vec<u32>> {
+        Ok(self.encode_plus_(corpus)?.ids)
+    }
+
+    // ids along with their tokens, offsets and masks, ready to be padded or truncated
+    fn encode_plus_(&self, corpus: &str) -> Result<Encoding> {
+        let encoding = Encoding::from_tokens(&self.transform_(corpus)?, self.get_vocabulary());
+        match self.post_processor() {
+            Some(template) => template.apply_(&encoding, None, self.get_vocabulary()),
+            None => Ok(encoding)
+        }
+    }
+
+    // two sequences in a single encoding, the tokens of the second one get
+    // the type id 1 unless a template says otherwise
+    fn encode_pair_(&self, first: &str, second: &str) -> Result<Encoding> {
+        let vocabulary = self.get_vocabulary();
+        let first = Encoding::from_tokens(&self.transform_(first)?, vocabulary);
+        let second = Encoding::from_tokens(&self.transform_(second)?, vocabulary);
+        match self.post_processor() {
+            Some(template) => template.apply_(&first, Some(&second), vocabulary),
+            None => Template::new_("$A", Some("$A $B:1"))?.apply_(&first, Some(&second), vocabulary)
+        }
+    }
+
+    // documents are processed in parallel, on the global thread pool
+    fn transform_batch_(&self, corpora: &[&str]) -> Result<Vec<Vec<Token>>> where Self: Sync {
+        corpora.par_iter().map(|corpus| self.transform_(corpus)).collect()
+    }
+
+    fn encode_batch_(&self, corpora: &[&str]) -> Result<Vec<Vec<u32>>> where Self: Sync {
+        corpora.par_iter().map(|corpus| self.encode_(corpus)).collect()
+    }
+
+    fn encode_plus_batch_(&self, corpora: &[&str]) -> Result<Vec<Encoding>> where Self: Sync {
+        corpora.par_iter().map(|corpus| self.encode_plus_(corpus)).collect()
+    }
+
+    fn decode_(&self, ids: &[u32], skip_special_tokens: bool) -> String {
+        let vocabulary = self.get_vocabulary();
+        let words: Vec<&str> = ids
+            .iter()
+            .map(|id| vocabulary.token(*id).map(|t| t.as_str()).unwrap_or(UNK))
+            .filter(|t| !(skip_special_tokens && vocabulary.is_special(t)))
+            .collect();
+        self.detokenize_words_(&words)
     }
 
-    fn tokenize(&self, vocabulary: &Vec<Word>, tokens: &Vec<Token>) -> Vec<Token> {
+    // bytes a token stands for once the markers of the model are removed, and
+    // whether it continues the word of the previous token
+    fn detokenize_word(&self, word: &str) -> (Vec<u8>, bool) {
+        (word.as_bytes().to_vec(), false)
+    }
+
+    // tokens know the text that follows them, so this gives back the text
+    // they were built from (as long as the pre-tokenizers kept the words as is)
+    fn detokenize_(&self, tokens: &[Token]) -> String {
+        let mut text: Vec<u8> = Vec::new();
+        for token in tokens.iter() {
+            text.extend(self.detokenize_word(&token.word).0);
+            text.extend(token.whitespace.as_bytes());
+        }
+
+        String::from_utf8_lossy(&text).into_owned()
+    }
+
+    // without whitespace information words are separated by a single space,
+    // except around punctuation and between the subwords of a word
+    fn detokenize_words_(&self, words: &[&str]) -> String {
+        let mut joined: Vec<Vec<u8>> = Vec::new();
+        for word in words.iter() {
+            let (bytes, continues) = self.detokenize_word(word);
+            let special = self.get_vocabulary().is_special(word);
+            match joined.last_mut() {
+                Some(last) if continues && !special => last.extend(bytes),
+                _ => joined.push(bytes)
+            }
+        }
+
+        let joined: Vec<String> = joined.iter().map(|w| String::from_utf8_lossy(w).into_owned()).collect();
+        let mut text = String::new();
+        for (i, word) in joined.iter().enumerate() {
+            if i > 0 && needs_space(&joined[i - 1], word) {
+                text.push(' ');
+            }
+            text.push_str(word);
+        }
+
+        text
+    }
+
+    // cut the special tokens out of the corpus, then run the rest through
+    // every normalizer and every pre-tokenizer, in order
+    fn pre_process(&self, corpus: &str) -> Result<Vec<Token>> {
+        let mut tokens: Vec<Token> = self.get_vocabulary().specials().split(corpus);
+        if !self.normalizers().is_empty() {
+            tokens = tokens
+                .into_iter()
+                .map(|t| if t.special { t } else { normalizers::normalize(self.normalizers(), &t) })
+                .collect();
+        }
+        for pre_tokenizer in self.pre_tokenizers().iter() {
+            tokens = pre_tokenizer.pre_tokenize_(tokens)?;
+        }
+
+        // keep what the pre-tokenizers dropped between tokens
+        let next_starts: Vec<usize> = tokens.iter().skip(1).map(|t| t.start).chain([corpus.len()]).collect();
+        for (token, next_start) in tokens.iter_mut().zip(next_starts) {
+            token.whitespace = corpus.get(token.end..next_start).unwrap_or("").to_string();
+        }
+
+        Ok(tokens)
+    }
+
+    fn extract_vocabulary(&self, tokens: &[Token]) -> Vec<Word> {
+        tokens.iter().map(|t| t.word.to_string()).unique().collect()
+    }
+
+    fn tokenize(&self, vocabulary: &Vocabulary, tokens: &[Token]) -> Vec<Token> {
         tokens.iter().map(|t| {
-            Token {
-                word: 
+            t.with_word(
                 if vocabulary.contains(&t.word) {t.word.to_string()}
                 else {UNK.to_string()}
-            }
+            )
         }).collect()
     }
 }
+
+// no space goes before closing punctuation, nor after opening punctuation
+fn needs_space(previous: &str, next: &str) -> bool {
+    let only = |word: &str, chars: &str| !word.is_empty() && word.chars().all(|c| chars.contains(c));
+    !(only(next, ".,:;!?)]}'-%") || only(previous, "([{'-$"))
+}
+
+// same as 'fit_iter_' for any Python iterable of strings (a list, a
+// generator), the GIL is only taken to get the next document
+pub fn fit_python_iter<T: Tokenizer + Send + Sync>(tokenizer: &mut T, py: Python, corpora: &PyAny) -> PyResult<()> {
+    let iterator: Py<PyIterator> = corpora.iter()?.into();
+    let corpora = std::iter::from_fn(|| {
+        Python::with_gil(|py| {
+            iterator
+                .as_ref(py)
+                .next()
+                .map(|corpus| corpus.and_then(|c| c.extract::<String>()).map_err(|e| Error::Python(Box::new(e))))
+        })
+    });
+
+    Ok(py.allow_threads(|| tokenizer.fit_iter_(corpora))?)
+}
+
+// the Python methods every tokenizer has, the ones specific to a tokenizer
+// (its constructor first) are given to the macro
+macro_rules! tokenizer_pymethods {
+    ($name:ident { $($methods:tt)* }) => {
+        #[pyo3::pymethods]
+        impl $name {
+            $($methods)*
+
+            fn fit(&mut self, corpus: &str) -> pyo3::PyResult<()> {
+                Ok($name::fit_(self, corpus)?)
+            }
+
+            // documents can come from any iterable of strings, such as a generator
+            fn fit_iter(&mut self, py: pyo3::Python, corpora: &pyo3::types::PyAny) -> pyo3::PyResult<()> {
+                $crate::tokenization::tokenizers::tokenizer::fit_python_iter(self, py, corpora)
+            }
+
+            // every line is a document, '.gz' files are decompressed
+            fn fit_files(&mut self, py: pyo3::Python, paths: Vec<String>) -> pyo3::PyResult<()> {
+                let paths: Vec<&str> = paths.iter().map(|p| p.as_str()).collect();
+                Ok(py.allow_threads(|| $name::fit_files_(self, &paths))?)
+            }
+
+            fn transform(&self, corpus: &str) -> pyo3::PyResult<Vec<$crate::tokenization::token::Token>> {
+                Ok($name::transform_(self, corpus)?)
+            }
+
+            fn fit_transform(&mut self, corpus: &str) -> pyo3::PyResult<Vec<$crate::tokenization::token::Token>> {
+                Ok($name::fit_transform_(self, corpus)?)
+            }
+
+            fn encode(&self, corpus: &str) -> pyo3::PyResult<Vec<u32>> {
+                Ok($name::encode_(self, corpus)?)
+            }
+
+            // the GIL is released while the documents are processed
+            fn transform_batch(
+                &self,
+                py: pyo3::Python,
+                corpora: Vec<String>
+            ) -> pyo3::PyResult<Vec<Vec<$crate::tokenization::token::Token>>> {
+                let corpora: Vec<&str> = corpora.iter().map(|c| c.as_str()).collect();
+                Ok(py.allow_threads(|| $name::transform_batch_(self, &corpora))?)
+            }
+
+            fn encode_batch(&self, py: pyo3::Python, corpora: Vec<String>) -> pyo3::PyResult<Vec<Vec<u32>>> {
+                let corpora: Vec<&str> = corpora.iter().map(|c| c.as_str()).collect();
+                Ok(py.allow_threads(|| $name::encode_batch_(self, &corpora))?)
+            }
+
+            fn encode_plus(&self, corpus: &str) -> pyo3::PyResult<$crate::tokenization::encoding::Encoding> {
+                Ok($name::encode_plus_(self, corpus)?)
+            }
+
+            fn encode_plus_batch(
+                &self,
+                py: pyo3::Python,
+                corpora: Vec<String>
+            ) -> pyo3::PyResult<Vec<$crate::tokenization::encoding::Encoding>> {
+                let corpora: Vec<&str> = corpora.iter().map(|c| c.as_str()).collect();
+                Ok(py.allow_threads(|| $name::encode_plus_batch_(self, &corpora))?)
+            }
+
+            fn encode_pair(&self, first: &str, second: &str) -> pyo3::PyResult<$crate::tokenization::encoding::Encoding> {
+                Ok($name::encode_pair_(self, first, second)?)
+            }
+
+            #[args(skip_special_tokens = "false")]
+            fn decode(&self, ids: Vec<u32>, skip_special_tokens: bool) -> String {
+                $name::decode_(self, &ids, skip_special_tokens)
+            }
+
+            fn detokenize(&self, tokens: Vec<$crate::tokenization::token::Token>) -> String {
+                $name::detokenize_(self, &tokens)
+            }
+
+            fn detokenize_words(&self, words: Vec<String>) -> String {
+                let words: Vec<&str> = words.iter().map(|w| w.as_str()).collect();
+                $name::detokenize_words_(self, &words)
+            }
+
+            fn token_to_id(&self, token: &str) -> Option<u32> {
+                self.get_vocabulary().id(token)
+            }
+
+            fn id_to_token(&self, id: u32) -> Option<String> {
+                self.get_vocabulary().token(id).cloned()
+            }
+
+            fn add_special_tokens(&mut self, tokens: Vec<String>) -> pyo3::PyResult<Vec<u32>> {
+                let tokens: Vec<&str> = tokens.iter().map(|t| t.as_str()).collect();
+                Ok($name::add_special_tokens_(self, &tokens)?)
+            }
+
+            #[getter]
+            fn special_tokens(&self) -> Vec<String> {
+                self.get_vocabulary().specials().tokens().to_vec()
+            }
+
+            #[getter]
+            fn post_processor(&self) -> Option<$crate::tokenization::template::Template> {
+                $crate::tokenization::tokenizers::tokenizer::Tokenizer::post_processor(self).cloned()
+            }
+
+            #[setter]
+            fn set_post_processor(&mut self, template: Option<$crate::tokenization::template::Template>) {
+                $name::set_post_processor_(self, template)
+            }
+
+            fn to_json(&self) -> pyo3::PyResult<String> {
+                Ok($name::to_json_(self)?)
+            }
+
+            #[staticmethod]
+            fn from_json(json: &str) -> pyo3::PyResult<Self> {
+                Ok($name::from_json_(json)?)
+            }
+
+            // the whole pipeline goes to a single JSON file
+            fn save(&self, path: &str) -> pyo3::PyResult<()> {
+                Ok($name::save_(self, path)?)
+            }
+
+            #[staticmethod]
+            fn load(path: &str) -> pyo3::PyResult<Self> {
+                Ok($name::load_(path)?)
+            }
+        }
+    };
+}
+
+pub(crate) use tokenizer_pymethods;
@@ -0,0 +1,680 @@
+use std::collections::{HashMap, HashSet};
+
+use pyo3::{pyclass, PyResult};
+use serde::{Deserialize, Serialize};
+use rand::prelude::*;
+
+use crate::tokenization::{
+    template::Template,
+    special_tokens,
+    tokenizers::{serialization::{Pipeline, Serializable}, tokenizer::{tokenizer_pymethods, Tokenizer}},
+    token::Token,
+    normalizers::NormalizerKind,
+    pre_tokenizers::PreTokenizerKind,
+    vocabulary::{Vocabulary, RESERVED},
+    word_counts::WordCounts
+};
+use crate::utils::errors::Result;
+
+// number of pieces kept from the substrings of the corpus before pruning
+const SEED_SIZE: usize = 1_000_000;
+// share of the vocabulary kept after each pruning step
+const SHRINKING_FACTOR: f64 = 0.75;
+const EM_ITERATIONS: usize = 2;
+// how much less likely than the rarest piece an unknown character is
+const UNK_PENALTY: f64 = 10.0;
+
+// a piece found in a word: byte range and id
+type Edge = (usize, usize, u32);
+// a partial path in the n-best search: score, previous position, rank there and last edge
+type Node = (f64, usize, usize, Option<Edge>);
+
+fn log_sum_exp(x: f64, y: f64) -> f64 {
+    if x == f64::NEG_INFINITY {
+        return y;
+    }
+    if y == f64::NEG_INFINITY {
+        return x;
+    }
+    let max = x.max(y);
+    max + ((x - max).exp() + (y - max).exp()).ln()
+}
+
+#[pyclass]
+pub struct Unigram {
+    // pieces and their log probability, the id of a piece is its index
+    pieces: Vec<(String, f64)>,
+    vocabulary: Vocabulary,
+    normalizers: Vec<NormalizerKind>,
+    pre_tokenizers: Vec<PreTokenizerKind>,
+    post_processor: Option<Template>,
+    vocab_size: u32,
+    max_piece_length: usize
+}
+
+// only the scores of the learned pieces are saved, the ids come from the vocabulary
+#[derive(Deserialize, Serialize)]
+pub struct Model {
+    vocab_size: u32,
+    max_piece_length: usize,
+    pieces: Vec<(String, f64)>,
+}
+
+impl Unigram {
+    fn new_(
+        vocab_size: u32,
+        pre_tokenizers: Option<&[PreTokenizerKind]>,
+        max_piece_length: usize
+    ) -> Self {
+        let pre_tokenizers = match pre_tokenizers {
+            Some(t) => t.to_vec(),
+            None => vec![]
+        };
+
+        let mut unigram = Unigram {
+            pieces: vec![],
+            vocabulary: Vocabulary::new(),
+            normalizers: vec![],
+            post_processor: None,
+            pre_tokenizers,
+            vocab_size,
+            max_piece_length
+        };
+        unigram.set_pieces(vec![]);
+        unigram
+    }
+
+    fn set_pieces(&mut self, pieces: Vec<(String, f64)>) {
+        let min_score = pieces.iter().map(|p| p.1).fold(0.0, f64::min);
+
+        // reserved and special tokens keep their ids and are never matched inside a word
+        self.vocabulary.reset(true);
+        let ids: Vec<u32> = pieces.iter().map(|p| self.vocabulary.add(&p.0)).collect();
+        let size = self.vocabulary.ids().values().max().map_or(0, |id| *id as usize + 1);
+        self.pieces = (0..size as u32)
+            .map(|id| (self.vocabulary.token(id).cloned().unwrap_or_default(), min_score - UNK_PENALTY))
+            .collect();
+        for (id, piece) in ids.into_iter().zip(pieces) {
+            self.pieces[id as usize] = piece;
+        }
+    }
+
+    // pieces found by the training, as opposed to reserved and special tokens
+    fn is_learned(&self, id: u32) -> bool {
+        id as usize >= RESERVED.len()
+            && self.vocabulary.token(id).is_some_and(|t| !self.vocabulary.is_special(t))
+    }
+
+    fn learned(&self) -> Vec<usize> {
+        (0..self.pieces.len()).filter(|id| self.is_learned(*id as u32)).collect()
+    }
+
+    // every piece of the vocabulary found in the word, grouped by start position.
+    // Characters that are not covered by any piece get an '<unk>' edge.
+    fn lattice(&self, word: &str, excluded: Option<u32>) -> Vec<Vec<Edge>> {
+        let starts: Vec<usize> = word.char_indices().map(|(i, _)| i).collect();
+        let mut lattice: Vec<Vec<Edge>> = vec![vec![]; starts.len()];
+
+        for (position, start) in starts.iter().enumerate() {
+            let mut has_single_char = false;
+            for length in 1..=self.max_piece_length {
+                if position + length > starts.len() {
+                    break;
+                }
+                let end = if position + length < starts.len() { starts[position + length] } else { word.len() };
+
+                if let Some(id) = self.vocabulary.id(&word[*start..end]) {
+                    if self.is_learned(id) && Some(id) != excluded {
+                        lattice[position].push((*start, end, id));
+                        has_single_char |= length == 1;
+                    }
+                }
+            }
+            if !has_single_char {
+                let end = if position + 1 < starts.len() { starts[position + 1] } else { word.len() };
+                lattice[position].push((*start, end, 0));
+            }
+        }
+
+        lattice
+    }
+
+    fn score(&self, id: u32) -> f64 {
+        self.pieces[id as usize].1
+    }
+
+    // position in characters of every byte offset that starts a character
+    fn positions(word: &str) -> HashMap<usize, usize> {
+        let mut positions: HashMap<usize, usize> = word.char_indices().enumerate().map(|(p, (i, _))| (i, p)).collect();
+        positions.insert(word.len(), positions.len());
+        positions
+    }
+
+    // most likely segmentation of a word
+    fn viterbi(&self, word: &str, excluded: Option<u32>) -> (Vec<Edge>, f64) {
+        let lattice = self.lattice(word, excluded);
+        let positions = Unigram::positions(word);
+
+        let mut best: Vec<(f64, Option<Edge>)> = vec![(f64::NEG_INFINITY, None); lattice.len() + 1];
+        best[0].0 = 0.0;
+        for position in 0..lattice.len() {
+            if best[position].0 == f64::NEG_INFINITY {
+                continue;
+            }
+            for edge in lattice[position].iter() {
+                let end = positions[&edge.1];
+                let score = best[position].0 + self.score(edge.2);
+                if score > best[end].0 {
+                    best[end] = (score, Some(*edge));
+                }
+            }
+        }
+
+        let mut path: Vec<Edge> = Vec::new();
+        let mut position = lattice.len();
+        while let Some(edge) = best[position].1 {
+            path.push(edge);
+            position = positions[&edge.0];
+        }
+        path.reverse();
+
+        (path, best[lattice.len()].0)
+    }
+
+    // log of the summed probability of all paths reaching each position
+    fn forward(&self, lattice: &[Vec<Edge>], positions: &HashMap<usize, usize>, alpha: f64) -> Vec<f64> {
+        let mut forward: Vec<f64> = vec![f64::NEG_INFINITY; lattice.len() + 1];
+        forward[0] = 0.0;
+        for position in 0..lattice.len() {
+            for edge in lattice[position].iter() {
+                let end = positions[&edge.1];
+                forward[end] = log_sum_exp(forward[end], forward[position] + alpha * self.score(edge.2));
+            }
+        }
+
+        forward
+    }
+
+    // expected number of times each piece is used in the word, weighted by 'count'
+    fn expected_counts(&self, word: &str, count: f64, expected: &mut [f64]) {
+        let lattice = self.lattice(word, None);
+        let positions = Unigram::positions(word);
+        let forward = self.forward(&lattice, &positions, 1.0);
+
+        let mut backward: Vec<f64> = vec![f64::NEG_INFINITY; lattice.len() + 1];
+        backward[lattice.len()] = 0.0;
+        for position in (0..lattice.len()).rev() {
+            for edge in lattice[position].iter() {
+                let end = positions[&edge.1];
+                backward[position] = log_sum_exp(backward[position], self.score(edge.2) + backward[end]);
+            }
+        }
+
+        let z = forward[lattice.len()];
+        for (position, edges) in lattice.iter().enumerate() {
+            for edge in edges.iter() {
+                let end = positions[&edge.1];
+                let probability = (forward[position] + self.score(edge.2) + backward[end] - z).exp();
+                expected[edge.2 as usize] += count * probability;
+            }
+        }
+    }
+
+    // sample a segmentation from the whole lattice (forward-filtering backward-sampling)
+    fn sample_lattice(&self, word: &str, alpha: f64, rng: &mut impl Rng) -> Vec<Edge> {
+        let lattice = self.lattice(word, None);
+        let positions = Unigram::positions(word);
+        let forward = self.forward(&lattice, &positions, alpha);
+
+        let mut ending: Vec<Vec<(usize, Edge)>> = vec![vec![]; lattice.len() + 1];
+        for (position, edges) in lattice.iter().enumerate() {
+            for edge in edges.iter() {
+                ending[positions[&edge.1]].push((position, *edge));
+            }
+        }
+
+        let mut path: Vec<Edge> = Vec::new();
+        let mut position = lattice.len();
+        while position > 0 {
+            let weights: Vec<f64> = ending[position]
+                .iter()
+                .map(|(start, edge)| (forward[*start] + alpha * self.score(edge.2) - forward[position]).exp())
+                .collect();
+            let mut threshold = rng.gen::<f64>() * weights.iter().sum::<f64>();
+
+            let mut chosen = ending[position].len() - 1;
+            for (i, weight) in weights.iter().enumerate() {
+                if threshold < *weight {
+                    chosen = i;
+                    break;
+                }
+                threshold -= weight;
+            }
+
+            let (start, edge) = ending[position][chosen];
+            path.push(edge);
+            position = start;
+        }
+        path.reverse();
+
+        path
+    }
+
+    // the 'n' best segmentations of a word along with their score
+    fn nbest(&self, word: &str, n: usize) -> Vec<(Vec<Edge>, f64)> {
+        let lattice = self.lattice(word, None);
+        let positions = Unigram::positions(word);
+
+        let mut best: Vec<Vec<Node>> = vec![vec![]; lattice.len() + 1];
+        best[0].push((0.0, 0, 0, None));
+        for position in 0..lattice.len() {
+            best[position].sort_by(|x, y| y.0.total_cmp(&x.0));
+            best[position].truncate(n);
+            for rank in 0..best[position].len() {
+                for edge in lattice[position].iter() {
+                    let score = best[position][rank].0 + self.score(edge.2);
+                    best[positions[&edge.1]].push((score, position, rank, Some(*edge)));
+                }
+            }
+        }
+        let last = lattice.len();
+        best[last].sort_by(|x, y| y.0.total_cmp(&x.0));
+        best[last].truncate(n);
+
+        best[last]
+            .iter()
+            .map(|node| {
+                let score = node.0;
+                let mut path: Vec<Edge> = Vec::new();
+                let mut node = *node;
+                while let Some(edge) = node.3 {
+                    path.push(edge);
+                    node = best[node.1][node.2];
+                }
+                path.reverse();
+                (path, score)
+            })
+            .collect()
+    }
+
+    // subword regularization: 'nbest_size' <= 0 samples from all the segmentations,
+    // otherwise from the 'nbest_size' best ones. 'alpha' smooths the distribution.
+    pub fn sample_word(&self, word: &str, alpha: f64, nbest_size: i32, rng: &mut impl Rng) -> Vec<String> {
+        let path = self.sample_path(word, alpha, nbest_size, rng);
+        self.to_pieces(word, &path)
+    }
+
+    fn sample_path(&self, word: &str, alpha: f64, nbest_size: i32, rng: &mut impl Rng) -> Vec<Edge> {
+        match nbest_size {
+            n if n <= 0 => self.sample_lattice(word, alpha, rng),
+            1 => self.viterbi(word, None).0,
+            n => {
+                let candidates = self.nbest(word, n as usize);
+                let weights: Vec<f64> = candidates.iter().map(|c| (alpha * c.1).exp()).collect();
+                match rand::distributions::WeightedIndex::new(&weights) {
+                    Ok(dist) => candidates[dist.sample(rng)].0.to_vec(),
+                    Err(_) => self.viterbi(word, None).0
+                }
+            }
+        }
+    }
+
+    pub fn tokenize_word(&self, word: &str) -> Vec<String> {
+        let (path, _) = self.viterbi(word, None);
+        self.to_pieces(word, &path)
+    }
+
+    fn to_pieces(&self, word: &str, path: &[Edge]) -> Vec<String> {
+        self.to_spans(word, path).into_iter().map(|span| span.0).collect()
+    }
+
+    // pieces of a path and their byte range in the word, consecutive unknown
+    // characters are fused into a single '<unk>'
+    fn to_spans(&self, word: &str, path: &[Edge]) -> Vec<(String, usize, usize)> {
+        let mut spans: Vec<(String, usize, usize)> = Vec::new();
+        for edge in path.iter() {
+            match spans.last_mut() {
+                Some(last) if edge.2 == 0 && last.0 == special_tokens::UNK => last.2 = edge.1,
+                _ if edge.2 == 0 => spans.push((special_tokens::UNK.to_string(), edge.0, edge.1)),
+                _ => spans.push((word[edge.0..edge.1].to_string(), edge.0, edge.1))
+            }
+        }
+
+        spans
+    }
+
+    fn to_tokens(&self, token: &Token, path: &[Edge]) -> Vec<Token> {
+        token.split(self.to_spans(&token.word, path))
+    }
+
+    // all the substrings of the words, the most frequent and longest ones first
+    fn seed_pieces(&self, words: &HashMap<&str, u64>) -> Vec<(String, f64)> {
+        let mut counts: HashMap<&str, u64> = HashMap::new();
+        for (word, count) in words.iter() {
+            let starts: Vec<usize> = word.char_indices().map(|(i, _)| i).chain([word.len()]).collect();
+            for i in 0..starts.len() - 1 {
+                for j in (i + 1)..starts.len().min(i + 1 + self.max_piece_length) {
+                    *counts.entry(&word[starts[i]..starts[j]]).or_insert(0) += count;
+                }
+            }
+        }
+
+        let mut seeds: Vec<(&str, u64)> = counts.into_iter().collect();
+        seeds.sort_by(|x, y| {
+            let score = |s: &(&str, u64)| s.1 * s.0.chars().count() as u64;
+            score(y).cmp(&score(x)).then(x.0.cmp(y.0))
+        });
+
+        let mut pieces: Vec<(&str, u64)> = Vec::new();
+        let mut others = 0;
+        for seed in seeds {
+            // every character is kept so that no word is left uncovered
+            if seed.0.chars().count() == 1 || others < SEED_SIZE {
+                if seed.0.chars().count() > 1 {
+                    others += 1;
+                }
+                pieces.push(seed);
+            }
+        }
+
+        let total: f64 = pieces.iter().map(|p| p.1 as f64).sum();
+        pieces.into_iter().map(|(piece, count)| (piece.to_string(), (count as f64 / total).ln())).collect()
+    }
+
+    fn run_em(&mut self, words: &HashMap<&str, u64>) {
+        for _ in 0..EM_ITERATIONS {
+            let mut expected: Vec<f64> = vec![0.0; self.pieces.len()];
+            for (word, count) in words.iter() {
+                self.expected_counts(word, *count as f64, &mut expected);
+            }
+
+            let learned = self.learned();
+            let total: f64 = learned.iter().map(|id| expected[*id]).sum();
+            let pieces: Vec<(String, f64)> = learned
+                .iter()
+                .map(|id| (&self.pieces[*id], expected[*id]))
+                .filter(|(piece, count)| *count > 0.0 || piece.0.chars().count() == 1)
+                .map(|(piece, count)| (piece.0.to_owned(), (count.max(f64::MIN_POSITIVE) / total).ln()))
+                .collect();
+            self.set_pieces(pieces);
+        }
+    }
+
+    // drop the pieces whose removal hurts the likelihood the least
+    fn prune(&mut self, words: &HashMap<&str, u64>) {
+        let learned = self.learned();
+        let mut frequencies: Vec<f64> = vec![0.0; self.pieces.len()];
+        for (word, count) in words.iter() {
+            for edge in self.viterbi(word, None).0 {
+                frequencies[edge.2 as usize] += *count as f64;
+            }
+        }
+
+        let mut losses: Vec<(f64, usize)> = Vec::new();
+        for id in learned.iter().copied() {
+            let (piece, score) = &self.pieces[id];
+            if piece.chars().count() == 1 {
+                continue;
+            }
+            let (_, alternative) = self.viterbi(piece, Some(id as u32));
+            losses.push((frequencies[id] * (score - alternative), id));
+        }
+        losses.sort_by(|x, y| y.0.total_cmp(&x.0).then(x.1.cmp(&y.1)));
+
+        let size = learned.len();
+        let others = self.vocabulary.len() - size;
+        let target = ((size as f64 * SHRINKING_FACTOR) as usize).max((self.vocab_size as usize).saturating_sub(others));
+        let to_remove = size.saturating_sub(target).min(losses.len());
+
+        let removed: HashSet<usize> = losses.iter().rev().take(to_remove).map(|l| l.1).collect();
+        let pieces: Vec<(String, f64)> = learned
+            .iter()
+            .filter(|id| !removed.contains(id))
+            .map(|id| self.pieces[*id].to_owned())
+            .collect();
+        self.set_pieces(pieces);
+    }
+}
+
+impl Tokenizer for Unigram {
+    fn normalizers(&self) -> &[NormalizerKind] {
+        &self.normalizers
+    }
+
+    fn post_processor(&self) -> Option<&Template> {
+        self.post_processor.as_ref()
+    }
+
+    fn post_processor_mut(&mut self) -> &mut Option<Template> {
+        &mut self.post_processor
+    }
+
+    fn pre_tokenizers(&self) -> &[PreTokenizerKind] {
+        &self.pre_tokenizers
+    }
+
+    fn get_vocabulary(&self) -> &Vocabulary {
+        &self.vocabulary
+    }
+
+    fn get_vocabulary_mut(&mut self) -> &mut Vocabulary {
+        &mut self.vocabulary
+    }
+
+    fn fit_counts_(&mut self, counts: &WordCounts) {
+        let words: HashMap<&str, u64> = counts.as_map();
+
+        let seeds = self.seed_pieces(&words);
+        self.set_pieces(seeds);
+
+        loop {
+            self.run_em(&words);
+            if self.vocabulary.len() <= self.vocab_size as usize {
+                break;
+            }
+            let size = self.vocabulary.len();
+            self.prune(&words);
+            if self.vocabulary.len() == size {
+                break;
+            }
+        }
+    }
+
+    fn transform_(&self, corpus: &str) -> Result<Vec<Token>> {
+        Ok(self.pre_process(corpus)?
+            .iter()
+            .flat_map(|token| {
+                if token.special {
+                    return vec![token.clone()];
+                }
+                self.to_tokens(token, &self.viterbi(&token.word, None).0)
+            })
+            .collect())
+    }
+}
+
+impl Serializable for Unigram {
+    const NAME: &'static str = "Unigram";
+
+    type Model = Model;
+
+    fn model(&self) -> Model {
+        Model {
+            vocab_size: self.vocab_size,
+            max_piece_length: self.max_piece_length,
+            pieces: self.learned().into_iter().map(|id| self.pieces[id].clone()).collect(),
+        }
+    }
+
+    fn from_pipeline(pipeline: Pipeline<Model>) -> Result<Self> {
+        let min_score = pipeline.model.pieces.iter().map(|p| p.1).fold(0.0, f64::min);
+        let scores: HashMap<String, f64> = pipeline.model.pieces.into_iter().collect();
+
+        let vocabulary = pipeline.vocabulary;
+        let size = vocabulary.ids().values().max().map_or(0, |id| *id as usize + 1);
+        let pieces: Vec<(String, f64)> = (0..size as u32)
+            .map(|id| {
+                let piece = vocabulary.token(id).cloned().unwrap_or_default();
+                let score = scores.get(&piece).copied().unwrap_or(min_score - UNK_PENALTY);
+                (piece, score)
+            })
+            .collect();
+
+        Ok(Unigram {
+            pieces,
+            vocabulary,
+            normalizers: pipeline.normalizers,
+            pre_tokenizers: pipeline.pre_tokenizers,
+            post_processor: pipeline.post_processor,
+            vocab_size: pipeline.model.vocab_size,
+            max_piece_length: pipeline.model.max_piece_length,
+        })
+    }
+}
+
+tokenizer_pymethods!(Unigram {
+    #[new]
+    #[args(max_piece_length = "16", normalizers = "Vec::new()")]
+    fn new(
+        vocab_size: u32,
+        pre_tokenizers: Vec<PreTokenizerKind>,
+        max_piece_length: usize,
+        normalizers: Vec<NormalizerKind>
+    ) -> Self {
+        let mut tokenizer = Unigram::new_(vocab_size, Some(&pre_tokenizers), max_piece_length);
+        tokenizer.normalizers = normalizers;
+        tokenizer
+    }
+
+    // tokenize with a sampled segmentation instead of the best one
+    #[args(alpha = "0.1", nbest_size = "-1")]
+    fn sample(&self, corpus: &str, alpha: f64, nbest_size: i32) -> PyResult<Vec<Token>> {
+        let mut rng = thread_rng();
+        Ok(self.pre_process(corpus)?
+            .iter()
+            .flat_map(|token| {
+                if token.special {
+                    return vec![token.clone()];
+                }
+                let path = self.sample_path(&token.word, alpha, nbest_size, &mut rng);
+                self.to_tokens(token, &path)
+            })
+            .collect())
+    }
+
+    #[getter]
+    fn vocabulary(&self) -> Vec<(String, f64)> {
+        self.pieces.iter().filter(|p| !p.0.is_empty()).cloned().collect()
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(tokens: &[Token]) -> Vec<&str> {
+        tokens.iter().map(|t| t.word.as_str()).collect()
+    }
+
+    fn with_pieces(pieces: &[(&str, f64)]) -> Unigram {
+        let mut tokenizer = Unigram::new_(0, Some(&[PreTokenizerKind::WhiteSpace]), 16);
+        tokenizer.set_pieces(pieces.iter().map(|(p, s)| (p.to_string(), *s)).collect());
+        tokenizer
+    }
+
+    #[test]
+    fn best_segmentation() {
+        let tokenizer = with_pieces(&[
+            ("a", -2.0), ("b", -2.0), ("c", -2.0),
+            ("ab", -3.0), ("bc", -1.0), ("abc", -5.0),
+        ]);
+
+        let result: Vec<Token> = tokenizer.transform_("abc").unwrap();
+        assert_eq!(words(&result), vec!["a", "bc"]);
+    }
+
+    #[test]
+    fn unknown_characters_are_fused() {
+        let tokenizer = with_pieces(&[("a", -1.0)]);
+
+        let result: Vec<Token> = tokenizer.transform_("axya").unwrap();
+        assert_eq!(words(&result), vec!["a", "<unk>", "a"]);
+
+        let offsets: Vec<(usize, usize)> = result.iter().map(|t| (t.start, t.end)).collect();
+        assert_eq!(offsets, vec![(0, 1), (1, 3), (3, 4)]);
+    }
+
+    #[test]
+    fn nbest_segmentations() {
+        let tokenizer = with_pieces(&[
+            ("a", -2.0), ("b", -2.0), ("ab", -1.0),
+        ]);
+
+        let nbest = tokenizer.nbest("ab", 3);
+        let segmentations: Vec<Vec<String>> = nbest.iter().map(|n| tokenizer.to_pieces("ab", &n.0)).collect();
+        assert_eq!(segmentations, vec![vec!["ab".to_string()], vec!["a".to_string(), "b".to_string()]]);
+        assert_eq!(nbest[0].1, -1.0);
+        assert_eq!(nbest[1].1, -4.0);
+    }
+
+    #[test]
+    fn nan_scores_do_not_panic() {
+        let tokenizer = with_pieces(&[
+            ("a", -2.0), ("b", f64::NAN), ("ab", -1.0),
+        ]);
+
+        let nbest = tokenizer.nbest("ab", 3);
+        assert_eq!(nbest.len(), 2);
+    }
+
+    #[test]
+    fn sampling_covers_the_word() {
+        let tokenizer = with_pieces(&[
+            ("a", -2.0), ("b", -2.0), ("ab", -1.0), ("abab", -1.5),
+        ]);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for nbest_size in [-1, 1, 2, 5] {
+            for _ in 0..20 {
+                let pieces = tokenizer.sample_word("abab", 0.5, nbest_size, &mut rng);
+                assert_eq!(pieces.concat(), "abab");
+            }
+        }
+    }
+
+    #[test]
+    fn training() {
+        let mut tokenizer = Unigram::new_(
+            15,
+            Some(&[PreTokenizerKind::WhiteSpace]),
+            16
+        );
+
+        tokenizer.fit_("hug hug hug hugs pug pun bun bun hugs").unwrap();
+        assert!(tokenizer.vocabulary.len() <= 15);
+        assert_eq!(tokenizer.pieces[0].0, special_tokens::UNK);
+        assert_eq!(tokenizer.vocabulary.len(), tokenizer.pieces.len());
+
+        let result: Vec<Token> = tokenizer.transform_("hugs bun").unwrap();
+        assert_eq!(words(&result).concat(), "hugsbun");
+        assert!(!words(&result).contains(&special_tokens::UNK));
+    }
+
+    #[test]
+    fn special_tokens() {
+        let mut tokenizer = Unigram::new_(
+            15,
+            Some(&[PreTokenizerKind::WhiteSpace]),
+            16
+        );
+
+        let id = tokenizer.add_special_tokens_(&["<pad>"]).unwrap()[0];
+        tokenizer.fit_("hug hug hug<pad> hugs pug pun bun bun hugs").unwrap();
+        assert_eq!(tokenizer.vocabulary.id("<pad>"), Some(id));
+        assert!(tokenizer.vocabulary.len() <= 15);
+
+        let result: Vec<Token> = tokenizer.transform_("bun<pad>").unwrap();
+        assert_eq!(words(&result).last(), Some(&"<pad>"));
+        assert_eq!(words(&result).concat(), "bun<pad>");
+    }
+}
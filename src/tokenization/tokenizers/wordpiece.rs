@@ -0,0 +1,449 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
+
+use pyo3::pyclass;
+use serde::{Deserialize, Serialize};
+
+use crate::tokenization::{
+    template::Template,
+    special_tokens,
+    tokenizers::{serialization::{Pipeline, Serializable}, tokenizer::{tokenizer_pymethods, Tokenizer}},
+    token::Token,
+    normalizers::NormalizerKind,
+    pre_tokenizers::PreTokenizerKind,
+    vocabulary::Vocabulary,
+    word_counts::WordCounts
+};
+use crate::utils::errors::Result;
+
+#[pyclass]
+pub struct WordPiece {
+    vocabulary: Vocabulary,
+    normalizers: Vec<NormalizerKind>,
+    pre_tokenizers: Vec<PreTokenizerKind>,
+    post_processor: Option<Template>,
+    vocab_size: u32,
+    // longer words are not split at all and become '<unk>'
+    max_input_chars_per_word: usize
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Model {
+    vocab_size: u32,
+    max_input_chars_per_word: usize,
+}
+
+impl WordPiece {
+    fn new_(
+        vocab_size: u32,
+        pre_tokenizers: Option<&[PreTokenizerKind]>,
+        max_input_chars_per_word: usize
+    ) -> Self {
+        let pre_tokenizers = match pre_tokenizers {
+            Some(t) => t.to_vec(),
+            None => vec![]
+        };
+
+        WordPiece {
+            vocabulary: Vocabulary::new(),
+            normalizers: vec![],
+            post_processor: None,
+            pre_tokenizers,
+            vocab_size,
+            max_input_chars_per_word
+        }
+    }
+
+    // greedy longest-match-first: take the longest known prefix, then the
+    // longest known continuation of what is left, and so on
+    pub fn tokenize_word(&self, word: &str) -> Vec<String> {
+        let chars: Vec<(usize, char)> = word.char_indices().collect();
+        if chars.len() > self.max_input_chars_per_word {
+            return vec![special_tokens::UNK.to_string()];
+        }
+
+        let mut pieces: Vec<String> = Vec::new();
+        let mut start = 0;
+        while start < chars.len() {
+            let mut end = chars.len();
+            let mut piece: Option<String> = None;
+
+            while start < end {
+                let from = chars[start].0;
+                let to = if end < chars.len() { chars[end].0 } else { word.len() };
+                let candidate = if start > 0 {
+                    special_tokens::CONTINUATION.to_string() + &word[from..to]
+                } else {
+                    word[from..to].to_string()
+                };
+
+                if self.vocabulary.contains(&candidate) {
+                    piece = Some(candidate);
+                    break;
+                }
+                end -= 1;
+            }
+
+            match piece {
+                Some(p) => pieces.push(p),
+                None => return vec![special_tokens::UNK.to_string()]
+            }
+            start = end;
+        }
+
+        pieces
+    }
+
+    fn split_word(word: &str) -> Vec<String> {
+        word.chars()
+            .enumerate()
+            .map(|(i, c)| {
+                if i == 0 { c.to_string() } else { special_tokens::CONTINUATION.to_string() + &c.to_string() }
+            })
+            .collect()
+    }
+
+    fn merge_pieces(left: &str, right: &str) -> String {
+        left.to_string() + right.strip_prefix(special_tokens::CONTINUATION).unwrap_or(right)
+    }
+
+    // start from single characters and keep merging the pair with the best
+    // likelihood gain, that is count(ab) / (count(a) * count(b)). Counts are
+    // only updated for the words a merge changes, and only the pairs around
+    // the merged pieces are scored again.
+    fn train(&self, words: &HashMap<&str, u64>) -> Vec<String> {
+        let mut words: Vec<(Vec<String>, u64)> = words
+            .iter()
+            .map(|(word, count)| (WordPiece::split_word(word), *count))
+            .collect();
+
+        let mut counts = PieceCounts::default();
+        for (index, (word, count)) in words.iter().enumerate() {
+            counts.add(index, word, *count);
+        }
+        let pairs: Vec<Pair> = counts.pairs.keys().cloned().collect();
+        for pair in pairs.iter() {
+            counts.push(pair);
+        }
+
+        let alphabet: BTreeSet<String> = words.iter().flat_map(|w| w.0.to_vec()).collect();
+        let mut known: HashSet<String> = alphabet.iter().cloned().collect();
+        let mut vocabulary: Vec<String> = alphabet.into_iter().collect();
+
+        while self.vocabulary.len() + vocabulary.len() < self.vocab_size as usize {
+            let pair = match counts.pop() {
+                Some(pair) => pair,
+                None => break
+            };
+            let merged = WordPiece::merge_pieces(&pair.0, &pair.1);
+
+            let mut indices: Vec<usize> = counts.words.remove(&pair).unwrap_or_default().into_iter().collect();
+            indices.sort_unstable();
+            for index in indices {
+                let (word, count) = &mut words[index];
+                counts.remove(index, word, *count);
+
+                let mut i = 0;
+                while i + 1 < word.len() {
+                    if word[i] == pair.0 && word[i + 1] == pair.1 {
+                        word[i] = merged.to_owned();
+                        word.remove(i + 1);
+                    }
+                    i += 1;
+                }
+                counts.add(index, word, *count);
+            }
+            counts.rescore(&[&pair.0, &pair.1, &merged]);
+            if known.insert(merged.to_owned()) {
+                vocabulary.push(merged);
+            }
+        }
+
+        vocabulary
+    }
+}
+
+type Pair = (String, String);
+
+// a candidate merge in the priority queue. Entries are never updated in place:
+// when the score of a pair changes a new entry is pushed and stale ones are
+// skipped when popped.
+#[derive(PartialEq)]
+struct Candidate {
+    score: f64,
+    count: u64,
+    pair: Pair,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    // best score first, then the most frequent pair, then lexicographic order
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then(self.count.cmp(&other.count))
+            .then_with(|| other.pair.cmp(&self.pair))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// how often every piece and pair of pieces appears in the training words,
+// which words each pair appears in and which pairs each piece is part of
+#[derive(Default)]
+struct PieceCounts {
+    symbols: HashMap<String, u64>,
+    pairs: HashMap<Pair, u64>,
+    words: HashMap<Pair, HashSet<usize>>,
+    neighbours: HashMap<String, HashSet<Pair>>,
+    queue: BinaryHeap<Candidate>,
+}
+
+impl PieceCounts {
+    fn add(&mut self, index: usize, word: &[String], count: u64) {
+        for symbol in word.iter() {
+            *self.symbols.entry(symbol.to_owned()).or_insert(0) += count;
+        }
+        for pair in word.windows(2) {
+            let pair = (pair[0].to_owned(), pair[1].to_owned());
+            *self.pairs.entry(pair.clone()).or_insert(0) += count;
+            self.words.entry(pair.clone()).or_default().insert(index);
+            self.neighbours.entry(pair.0.to_owned()).or_default().insert(pair.clone());
+            self.neighbours.entry(pair.1.to_owned()).or_default().insert(pair);
+        }
+    }
+
+    fn remove(&mut self, index: usize, word: &[String], count: u64) {
+        for symbol in word.iter() {
+            if let Some(c) = self.symbols.get_mut(symbol) {
+                *c -= count;
+            }
+        }
+        for pair in word.windows(2) {
+            let pair = (pair[0].to_owned(), pair[1].to_owned());
+            if let Some(indices) = self.words.get_mut(&pair) {
+                indices.remove(&index);
+            }
+            if let Some(c) = self.pairs.get_mut(&pair) {
+                *c -= count;
+                if *c == 0 {
+                    self.pairs.remove(&pair);
+                    self.words.remove(&pair);
+                    for symbol in [&pair.0, &pair.1] {
+                        if let Some(pairs) = self.neighbours.get_mut(symbol) {
+                            pairs.remove(&pair);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn score(&self, pair: &Pair) -> Option<(f64, u64)> {
+        let count = *self.pairs.get(pair)?;
+        Some((count as f64 / (self.symbols[&pair.0] * self.symbols[&pair.1]) as f64, count))
+    }
+
+    fn push(&mut self, pair: &Pair) {
+        if let Some((score, count)) = self.score(pair) {
+            self.queue.push(Candidate { score, count, pair: pair.clone() });
+        }
+    }
+
+    // a merge only changes the counts of the merged pieces, so only the pairs
+    // they are part of get a new score
+    fn rescore(&mut self, pieces: &[&str]) {
+        let pairs: HashSet<Pair> = pieces
+            .iter()
+            .filter_map(|piece| self.neighbours.get(*piece))
+            .flatten()
+            .cloned()
+            .collect();
+        for pair in pairs.iter() {
+            self.push(pair);
+        }
+    }
+
+    // pair with the best current score, the entries of pairs that changed
+    // since they were pushed have been replaced by fresh ones
+    fn pop(&mut self) -> Option<Pair> {
+        while let Some(candidate) = self.queue.pop() {
+            if self.score(&candidate.pair) == Some((candidate.score, candidate.count)) {
+                return Some(candidate.pair);
+            }
+        }
+        None
+    }
+}
+
+impl Tokenizer for WordPiece {
+    fn normalizers(&self) -> &[NormalizerKind] {
+        &self.normalizers
+    }
+
+    fn post_processor(&self) -> Option<&Template> {
+        self.post_processor.as_ref()
+    }
+
+    fn post_processor_mut(&mut self) -> &mut Option<Template> {
+        &mut self.post_processor
+    }
+
+    fn pre_tokenizers(&self) -> &[PreTokenizerKind] {
+        &self.pre_tokenizers
+    }
+
+    fn get_vocabulary(&self) -> &Vocabulary {
+        &self.vocabulary
+    }
+
+    fn get_vocabulary_mut(&mut self) -> &mut Vocabulary {
+        &mut self.vocabulary
+    }
+
+    fn detokenize_word(&self, word: &str) -> (Vec<u8>, bool) {
+        match word.strip_prefix(special_tokens::CONTINUATION) {
+            Some(rest) => (rest.as_bytes().to_vec(), true),
+            None => (word.as_bytes().to_vec(), false)
+        }
+    }
+
+    fn fit_counts_(&mut self, counts: &WordCounts) {
+        let words: HashMap<&str, u64> = counts.as_map();
+
+        self.vocabulary.reset(true);
+        let pieces = self.train(&words);
+        self.vocabulary.extend(pieces);
+    }
+
+    fn transform_(&self, corpus: &str) -> Result<Vec<Token>> {
+        Ok(self.pre_process(corpus)?
+            .iter()
+            .flat_map(|token| {
+                if token.special {
+                    return vec![token.clone()];
+                }
+                // pieces follow each other in the word, '<unk>' covers all of it
+                let mut cursor = 0;
+                token.split(
+                    self.tokenize_word(&token.word)
+                        .into_iter()
+                        .map(|piece| {
+                            let from = cursor;
+                            cursor = if piece == special_tokens::UNK {
+                                token.word.len()
+                            } else {
+                                cursor + piece.strip_prefix(special_tokens::CONTINUATION).unwrap_or(&piece).len()
+                            };
+                            (piece, from, cursor)
+                        })
+                        .collect()
+                )
+            })
+            .collect())
+    }
+}
+
+impl Serializable for WordPiece {
+    const NAME: &'static str = "WordPiece";
+
+    type Model = Model;
+
+    fn model(&self) -> Model {
+        Model { vocab_size: self.vocab_size, max_input_chars_per_word: self.max_input_chars_per_word }
+    }
+
+    fn from_pipeline(pipeline: Pipeline<Model>) -> Result<Self> {
+        Ok(WordPiece {
+            vocabulary: pipeline.vocabulary,
+            normalizers: pipeline.normalizers,
+            pre_tokenizers: pipeline.pre_tokenizers,
+            post_processor: pipeline.post_processor,
+            vocab_size: pipeline.model.vocab_size,
+            max_input_chars_per_word: pipeline.model.max_input_chars_per_word,
+        })
+    }
+}
+
+tokenizer_pymethods!(WordPiece {
+    #[new]
+    #[args(max_input_chars_per_word = "100", normalizers = "Vec::new()")]
+    fn new(
+        vocab_size: u32,
+        pre_tokenizers: Vec<PreTokenizerKind>,
+        max_input_chars_per_word: usize,
+        normalizers: Vec<NormalizerKind>
+    ) -> Self {
+        let mut tokenizer = WordPiece::new_(vocab_size, Some(&pre_tokenizers), max_input_chars_per_word);
+        tokenizer.normalizers = normalizers;
+        tokenizer
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(tokens: &[Token]) -> Vec<&str> {
+        tokens.iter().map(|t| t.word.as_str()).collect()
+    }
+
+    fn with_vocabulary(vocabulary: &[&str], max_input_chars_per_word: usize) -> WordPiece {
+        let mut tokenizer = WordPiece::new_(
+            0,
+            Some(&[PreTokenizerKind::WhiteSpace]),
+            max_input_chars_per_word
+        );
+        tokenizer.vocabulary.extend(vocabulary.iter().map(|t| t.to_string()));
+        tokenizer
+    }
+
+    #[test]
+    fn longest_match_first() {
+        let tokenizer = with_vocabulary(&["un", "##aff", "##able", "##a", "affable"], 100);
+
+        let result: Vec<Token> = tokenizer.transform_("unaffable affable").unwrap();
+        assert_eq!(words(&result), vec!["un", "##aff", "##able", "affable"]);
+
+        let offsets: Vec<(usize, usize)> = result.iter().map(|t| (t.start, t.end)).collect();
+        assert_eq!(offsets, vec![(0, 2), (2, 5), (5, 9), (10, 17)]);
+    }
+
+    #[test]
+    fn unknown_words() {
+        let tokenizer = with_vocabulary(&["un", "##able"], 100);
+
+        let result: Vec<Token> = tokenizer.transform_("unable unknown").unwrap();
+        assert_eq!(words(&result), vec!["un", "##able", "<unk>"]);
+    }
+
+    #[test]
+    fn long_words() {
+        let tokenizer = with_vocabulary(&["a", "##a"], 3);
+
+        let result: Vec<Token> = tokenizer.transform_("aaa aaaa").unwrap();
+        assert_eq!(words(&result), vec!["a", "##a", "##a", "<unk>"]);
+    }
+
+    #[test]
+    fn training() {
+        let mut tokenizer = WordPiece::new_(
+            13,
+            Some(&[PreTokenizerKind::WhiteSpace]),
+            100
+        );
+
+        let result: Vec<Token> = tokenizer.fit_transform_("hug hug hugs pug").unwrap();
+        assert_eq!(tokenizer.vocabulary.len(), 13);
+        assert_eq!(tokenizer.encode_("hugs").unwrap(), vec![
+            tokenizer.vocabulary.id("hug").unwrap(),
+            tokenizer.vocabulary.id("##s").unwrap(),
+        ]);
+        assert_eq!(words(&result), vec!["hug", "hug", "hug", "##s", "pug"]);
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::tokenization::{special_tokens::{self, SpecialTokens}, token::Word};
+
+// tokens that get the first ids of every vocabulary built with 'Vocabulary::new'
+pub const RESERVED: [&str; 5] = [
+    special_tokens::UNK,
+    special_tokens::SOS,
+    special_tokens::EOS,
+    special_tokens::SOW,
+    special_tokens::EOW,
+];
+
+// bidirectional mapping between tokens and their ids. Ids are given in
+// insertion order and never change once a token is added.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(into = "Spec", from = "Spec")]
+pub struct Vocabulary {
+    tokens: Vec<Word>,
+    ids: HashMap<Word, u32>,
+    reserved: usize,
+    // unused ids, given to the next added tokens
+    holes: BTreeSet<u32>,
+    specials: SpecialTokens,
+}
+
+// saved form of a vocabulary: its tokens sorted by id, unused ids are skipped
+#[derive(Deserialize, Serialize)]
+struct Spec {
+    tokens: Vec<(Word, u32)>,
+    reserved: usize,
+    special_tokens: Vec<Word>,
+}
+
+impl From<Vocabulary> for Spec {
+    fn from(vocabulary: Vocabulary) -> Self {
+        let mut tokens: Vec<(Word, u32)> = vocabulary.ids.into_iter().collect();
+        tokens.sort_by_key(|(_, id)| *id);
+        Spec { tokens, reserved: vocabulary.reserved, special_tokens: vocabulary.specials.tokens().to_vec() }
+    }
+}
+
+impl From<Spec> for Vocabulary {
+    fn from(spec: Spec) -> Self {
+        let mut vocabulary = Vocabulary::from_ids(spec.tokens.into_iter().collect());
+        vocabulary.reserved = spec.reserved;
+        for token in spec.special_tokens {
+            vocabulary.add_special(&token);
+        }
+        vocabulary
+    }
+}
+
+impl Vocabulary {
+    pub fn new() -> Self {
+        let mut vocabulary = Vocabulary::empty();
+        vocabulary.extend(RESERVED.iter().map(|t| t.to_string()));
+        vocabulary.reserved = RESERVED.len();
+        vocabulary
+    }
+
+    // a vocabulary without any reserved token
+    pub fn empty() -> Self {
+        Vocabulary::default()
+    }
+
+    // build a vocabulary with existing ids, missing ids are left unused
+    pub fn from_ids(ids: HashMap<Word, u32>) -> Self {
+        let size = ids.values().max().map(|id| *id as usize + 1).unwrap_or(0);
+        let mut tokens: Vec<Word> = vec![String::new(); size];
+        for (token, id) in ids.iter() {
+            tokens[*id as usize] = token.to_owned();
+        }
+        let holes: BTreeSet<u32> = (0..size as u32).filter(|id| ids.get(&tokens[*id as usize]) != Some(id)).collect();
+
+        Vocabulary { tokens, ids, reserved: 0, holes, specials: SpecialTokens::default() }
+    }
+
+    // add a token if it is not known yet, and return its id
+    pub fn add(&mut self, token: &str) -> u32 {
+        if let Some(id) = self.ids.get(token) {
+            return *id;
+        }
+
+        let id = match self.holes.pop_first() {
+            Some(id) => {
+                self.tokens[id as usize] = token.to_string();
+                id
+            },
+            None => {
+                self.tokens.push(token.to_string());
+                self.tokens.len() as u32 - 1
+            }
+        };
+        self.ids.insert(token.to_string(), id);
+        id
+    }
+
+    // special tokens keep their id when the vocabulary is reset
+    pub fn add_special(&mut self, token: &str) -> u32 {
+        self.specials.add(token);
+        self.add(token)
+    }
+
+    pub fn is_special(&self, token: &str) -> bool {
+        self.specials.contains(token)
+    }
+
+    pub fn specials(&self) -> &SpecialTokens {
+        &self.specials
+    }
+
+    // forget every learned token, only the special tokens (and the reserved
+    // ones when asked) are kept, with the same ids
+    pub fn reset(&mut self, reserved: bool) {
+        let mut kept: HashMap<Word, u32> = self.specials
+            .tokens()
+            .iter()
+            .filter_map(|t| self.id(t).map(|id| (t.to_owned(), id)))
+            .collect();
+        if reserved {
+            let taken: Vec<u32> = kept.values().copied().collect();
+            for (id, token) in RESERVED.iter().enumerate() {
+                if !taken.contains(&(id as u32)) {
+                    kept.entry(token.to_string()).or_insert(id as u32);
+                }
+            }
+        }
+
+        let specials = std::mem::take(&mut self.specials);
+        *self = Vocabulary::from_ids(kept);
+        self.specials = specials;
+        self.reserved = if reserved { RESERVED.len() } else { 0 };
+    }
+
+    pub fn extend<I: IntoIterator<Item = Word>>(&mut self, tokens: I) {
+        for token in tokens {
+            self.add(&token);
+        }
+    }
+
+    pub fn id(&self, token: &str) -> Option<u32> {
+        self.ids.get(token).copied()
+    }
+
+    pub fn token(&self, id: u32) -> Option<&Word> {
+        self.tokens.get(id as usize).filter(|t| self.ids.get(*t) == Some(&id))
+    }
+
+    pub fn contains(&self, token: &str) -> bool {
+        self.ids.contains_key(token)
+    }
+
+    pub fn unk_id(&self) -> Option<u32> {
+        self.id(special_tokens::UNK)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn ids(&self) -> &HashMap<Word, u32> {
+        &self.ids
+    }
+
+    // tokens that were added on top of the reserved and special ones, in order
+    pub fn words(&self) -> Vec<Word> {
+        self.tokens[self.reserved.min(self.tokens.len())..]
+            .iter()
+            .filter(|t| self.ids.contains_key(*t) && !self.is_special(t))
+            .map(|t| t.to_owned())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserved_ids() {
+        let vocabulary = Vocabulary::new();
+
+        assert_eq!(vocabulary.id(special_tokens::UNK), Some(0));
+        assert_eq!(vocabulary.id(special_tokens::SOS), Some(1));
+        assert_eq!(vocabulary.id(special_tokens::EOS), Some(2));
+        assert!(vocabulary.words().is_empty());
+    }
+
+    #[test]
+    fn bidirectional() {
+        let mut vocabulary = Vocabulary::new();
+        vocabulary.extend(vec!["hello".to_string(), "world".to_string(), "hello".to_string()]);
+
+        assert_eq!(vocabulary.len(), RESERVED.len() + 2);
+        assert_eq!(vocabulary.id("world"), Some(6));
+        assert_eq!(vocabulary.token(6), Some(&"world".to_string()));
+        assert_eq!(vocabulary.token(7), None);
+        assert_eq!(vocabulary.words(), vec!["hello", "world"]);
+    }
+
+    #[test]
+    fn existing_ids() {
+        let vocabulary = Vocabulary::from_ids(HashMap::from([
+            ("a".to_string(), 0),
+            ("c".to_string(), 2),
+        ]));
+
+        assert_eq!(vocabulary.token(2), Some(&"c".to_string()));
+        assert_eq!(vocabulary.token(1), None);
+        assert_eq!(vocabulary.unk_id(), None);
+    }
+
+    #[test]
+    fn special_tokens_keep_their_ids() {
+        let mut vocabulary = Vocabulary::new();
+        vocabulary.extend(vec!["hello".to_string(), "world".to_string()]);
+        let pad = vocabulary.add_special("<pad>");
+
+        vocabulary.reset(true);
+        assert_eq!(vocabulary.id("<pad>"), Some(pad));
+        assert_eq!(vocabulary.id("hello"), None);
+        assert_eq!(vocabulary.unk_id(), Some(0));
+
+        vocabulary.extend(vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        assert_eq!(vocabulary.id("a"), Some(RESERVED.len() as u32));
+        assert_eq!(vocabulary.id("c"), Some(pad + 1));
+        assert_eq!(vocabulary.words(), vec!["a", "b", "c"]);
+    }
+}
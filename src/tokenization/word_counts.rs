@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use crate::tokenization::token::{Token, Word};
+
+// occurrences of the words of a corpus, in the order they first appear so
+// that counts merged from several documents do not depend on hashing
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WordCounts {
+    counts: Vec<(Word, u64)>,
+    positions: HashMap<Word, usize>,
+}
+
+impl WordCounts {
+    // special tokens are not counted
+    pub fn from_tokens(tokens: &[Token]) -> Self {
+        let mut counts = WordCounts::default();
+        for token in tokens.iter().filter(|t| !t.special) {
+            counts.add(&token.word, 1);
+        }
+        counts
+    }
+
+    pub fn add(&mut self, word: &str, count: u64) {
+        match self.positions.get(word) {
+            Some(position) => self.counts[*position].1 += count,
+            None => {
+                self.positions.insert(word.to_string(), self.counts.len());
+                self.counts.push((word.to_string(), count));
+            }
+        }
+    }
+
+    // the words of 'other' that are new go after the known ones
+    pub fn merge(&mut self, other: &WordCounts) {
+        for (word, count) in other.counts.iter() {
+            self.add(word, *count);
+        }
+    }
+
+    pub fn get(&self, word: &str) -> u64 {
+        self.positions.get(word).map_or(0, |position| self.counts[*position].1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(Word, u64)> {
+        self.counts.iter()
+    }
+
+    pub fn as_map(&self) -> HashMap<&str, u64> {
+        self.counts.iter().map(|(word, count)| (word.as_str(), *count)).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merged_counts() {
+        let mut counts = WordCounts::from_tokens(&[Token::new("b"), Token::new("a"), Token::new("b")]);
+        let mut other = WordCounts::default();
+        other.add("c", 2);
+        other.add("a", 1);
+        counts.merge(&other);
+
+        let words: Vec<(Word, u64)> = counts.iter().cloned().collect();
+        assert_eq!(words, vec![("b".to_string(), 2), ("a".to_string(), 2), ("c".to_string(), 2)]);
+        assert_eq!(counts.get("d"), 0);
+    }
+}
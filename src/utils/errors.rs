@@ -0,0 +1,45 @@
+use std::{error, fmt, io};
+
+use pyo3::{exceptions::{PyRuntimeError, PyValueError}, PyErr};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    // content that could not be understood, raised as a 'ValueError' in Python
+    Invalid(String),
+    // raised by Python code called from Rust (a callable pre-tokenizer) and
+    // given back to Python as is. Boxed so that only the Python side needs
+    // the interpreter.
+    Python(Box<dyn error::Error + Send + Sync>),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Invalid(message) => write!(f, "{}", message),
+            Error::Python(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<Error> for PyErr {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Io(e) => e.into(),
+            Error::Invalid(message) => PyValueError::new_err(message),
+            Error::Python(e) => match e.downcast::<PyErr>() {
+                Ok(e) => *e,
+                Err(e) => PyRuntimeError::new_err(e.to_string()),
+            },
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+
+use flate2::read::MultiGzDecoder;
+
+use crate::utils::errors::Result;
+
+// lines of every file, one after the other, without loading them in memory.
+// Files ending with '.gz' are decompressed on the fly.
+pub fn read_lines(paths: &[&str]) -> Result<impl Iterator<Item = Result<String>>> {
+    let mut readers: Vec<BufReader<Box<dyn Read + Send>>> = Vec::new();
+    for path in paths.iter() {
+        let file = File::open(path)?;
+        let reader: Box<dyn Read + Send> = match path.ends_with(".gz") {
+            true => Box::new(MultiGzDecoder::new(file)),
+            false => Box::new(file)
+        };
+        readers.push(BufReader::new(reader));
+    }
+
+    Ok(readers.into_iter().flat_map(|reader| reader.lines().map(|line| Ok(line?))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+
+    #[test]
+    fn plain_and_compressed_files() {
+        let directory = tempfile::tempdir().unwrap();
+        let plain = directory.path().join("lines.txt");
+        let compressed = directory.path().join("lines.txt.gz");
+        std::fs::write(&plain, "a b\nc\n").unwrap();
+        let mut encoder = GzEncoder::new(File::create(&compressed).unwrap(), Compression::default());
+        encoder.write_all(b"d e\n").unwrap();
+        encoder.finish().unwrap();
+
+        let lines: Vec<String> = read_lines(&[plain.to_str().unwrap(), compressed.to_str().unwrap()])
+            .unwrap()
+            .map(|line| line.unwrap())
+            .collect();
+        assert_eq!(lines, vec!["a b", "c", "d e"]);
+        assert!(read_lines(&["/nonexistent/dante"]).is_err());
+    }
+}
@@ -1 +1,3 @@
+pub mod errors;
+pub mod files;
 pub mod wrappers;
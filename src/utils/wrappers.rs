@@ -3,7 +3,7 @@ use crate::tokenization::{
     token::Word
 };
 
-pub fn wrap_sentence(sentence: &Vec<Word>, n: u32, start: bool, end: bool) -> Vec<Word> {
+pub fn wrap_sentence(sentence: &[Word], n: u32, start: bool, end: bool) -> Vec<Word> {
     let mut wrapped: Vec<Word> = Vec::new();
 
     if start {